@@ -0,0 +1,25 @@
+use sifis_td::Sifis;
+
+/// A set of hazards over many properties, similar to the ones of a complex device.
+pub fn sifis() -> Sifis {
+    (0u8..50)
+        .fold(Sifis::builder(), |builder, index| {
+            let level = format!("/properties/device{}/level", index % 20);
+            let on = format!("/properties/device{}/on", index % 20);
+            let mode = format!("/properties/device{}/mode", (index + 1) % 20);
+
+            builder
+                .fire_hazard(index % 10, |cond| {
+                    cond.when(level)
+                        .ge(f64::from(index) * 1.5)
+                        .and(on)
+                        .eq(true)
+                        .or(|cond| cond.when(mode).ne("eco"))
+                })
+                .explosion(index % 7, |cond| {
+                    cond.when(format!("/properties/device{}/pressure", index % 20))
+                        .gt(u32::from(index) * 10)
+                })
+        })
+        .build()
+}
@@ -0,0 +1,38 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sifis_td::{borrowed, risk, Sifis};
+
+mod common;
+
+/// The hazards of the evaluation benchmark, along with the declaration of their risks.
+fn sifis() -> Sifis {
+    Sifis {
+        risks: vec![risk::FIRE_HAZARD, risk::EXPLOSION],
+        ..common::sifis()
+    }
+}
+
+fn deserialize(c: &mut Criterion) {
+    let json = serde_json::to_string(&sifis()).unwrap();
+    let owned: Sifis = serde_json::from_str(&json).unwrap();
+    let borrowed: borrowed::Sifis<'_> = serde_json::from_str(&json).unwrap();
+    assert_eq!(borrowed.into_owned(), owned);
+
+    let mut group = c.benchmark_group("deserialize");
+    group.bench_function("owned", |b| {
+        b.iter(|| serde_json::from_str::<Sifis>(black_box(&json)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| serde_json::from_str::<borrowed::Sifis<'_>>(black_box(&json)).unwrap())
+    });
+    group.bench_function("borrowed_into_owned", |b| {
+        b.iter(|| {
+            serde_json::from_str::<borrowed::Sifis<'_>>(black_box(&json))
+                .unwrap()
+                .into_owned()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deserialize);
+criterion_main!(benches);
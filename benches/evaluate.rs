@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::json;
+
+mod common;
+
+fn state() -> serde_json::Value {
+    let properties: serde_json::Map<_, _> = (0..20)
+        .map(|index| {
+            (
+                format!("device{index}"),
+                json!({
+                    "level": index * 3,
+                    "on": index % 2 == 0,
+                    "mode": if index % 3 == 0 { "eco" } else { "turbo" },
+                    "pressure": index * 25,
+                }),
+            )
+        })
+        .collect();
+
+    json!({ "properties": properties })
+}
+
+fn evaluate(c: &mut Criterion) {
+    let sifis = common::sifis();
+    let compiled = sifis.compile();
+    let state = state();
+    assert_eq!(sifis.evaluate(&state), compiled.evaluate(&state));
+
+    let mut group = c.benchmark_group("evaluate");
+    group.bench_function("sifis", |b| b.iter(|| sifis.evaluate(black_box(&state))));
+    group.bench_function("compiled", |b| {
+        b.iter(|| compiled.evaluate(black_box(&state)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, evaluate);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+#ifndef SIFIS_TD_H
+#define SIFIS_TD_H
+
+/* This file is generated with cbindgen, do not edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of a function.
+//
+// The codes mirror the errors of the Rust library, and their values are stable.
+typedef enum SifisTdStatus {
+  // No error.
+  SIFIS_TD_STATUS_OK = 0,
+  // A required pointer is `NULL`.
+  SIFIS_TD_STATUS_NULL_POINTER = 1,
+  // A string is not valid UTF-8.
+  SIFIS_TD_STATUS_INVALID_UTF8 = 2,
+  // A string is not valid JSON.
+  SIFIS_TD_STATUS_INVALID_JSON = 3,
+  // A JSON document does not have the expected structure, i.e. a hazard with an unknown risk.
+  SIFIS_TD_STATUS_INVALID_DATA = 4,
+  // A JSON pointer of a JSON Patch is malformed.
+  SIFIS_TD_STATUS_PATCH_INVALID_POINTER = 5,
+  // The value to change or to read with a JSON Patch does not exist.
+  SIFIS_TD_STATUS_PATCH_NOT_FOUND = 6,
+  // A value of a JSON Patch cannot be placed in the target location.
+  SIFIS_TD_STATUS_PATCH_INVALID_TARGET = 7,
+  // A JSON Patch moves a value into one of its children.
+  SIFIS_TD_STATUS_PATCH_MOVE_INTO_CHILD = 8,
+  // A `test` operation of a JSON Patch failed.
+  SIFIS_TD_STATUS_PATCH_TEST_FAILED = 9,
+  // An unexpected internal error.
+  SIFIS_TD_STATUS_PANIC = 255,
+} SifisTdStatus;
+
+// An evaluator of the hazards of a Thing.
+typedef struct SifisTdEvaluator SifisTdEvaluator;
+
+// An active risk.
+typedef struct SifisTdRisk {
+  // The id of the risk, as in the Thing Description (i.e. `sho:FireHazard`).
+  char *id;
+  // The level of the risk.
+  uint8_t level;
+} SifisTdRisk;
+
+// An array of active risks, to be released with `sifis_td_risks_free`.
+typedef struct SifisTdRisks {
+  // The risks, `NULL` if there are none.
+  SifisTdRisk *risks;
+  // The number of risks.
+  size_t len;
+} SifisTdRisks;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates an evaluator for the hazards of a Thing Description.
+//
+// `td` is the JSON of the Thing Description, or of an object with its `sho:risks` and
+// `sho:hazards` only. `state` is the JSON of the initial state, with the same structure of the
+// Thing Description (i.e. `{"properties": {"brightness": 90}}`), or `NULL` for an empty state.
+//
+// On success, the evaluator is stored in `out`, and it must be released with
+// `sifis_td_evaluator_free`.
+//
+// # Safety
+//
+// `td` and `state` must be `NULL` or valid nul-terminated strings, `out` must be `NULL` or
+// valid for writes.
+SifisTdStatus sifis_td_evaluator_new(const char *td,
+                                     const char *state,
+                                     SifisTdEvaluator **out);
+
+// Releases an evaluator. Does nothing if `evaluator` is `NULL`.
+//
+// # Safety
+//
+// `evaluator` must be `NULL` or returned by `sifis_td_evaluator_new`, and it must not be used
+// afterwards.
+void sifis_td_evaluator_free(SifisTdEvaluator *evaluator);
+
+// Replaces the state of a Thing with a JSON document.
+//
+// # Safety
+//
+// `evaluator` must be `NULL` or a valid evaluator, `state` must be `NULL` or a valid
+// nul-terminated string.
+SifisTdStatus sifis_td_evaluator_set_state(SifisTdEvaluator *evaluator, const char *state);
+
+// Applies a JSON Patch (RFC 6902) to the state of a Thing.
+//
+// If an operation fails none of the operations is applied, and its index is stored in
+// `failed_operation` unless it is `NULL`.
+//
+// # Safety
+//
+// `evaluator` must be `NULL` or a valid evaluator, `patch` must be `NULL` or a valid
+// nul-terminated string, `failed_operation` must be `NULL` or valid for writes.
+SifisTdStatus sifis_td_evaluator_apply_patch(SifisTdEvaluator *evaluator,
+                                             const char *patch,
+                                             size_t *failed_operation);
+
+// Stores the risks triggered by the current state of a Thing in `out`, which must be released
+// with `sifis_td_risks_free`.
+//
+// Each risk is present once, with its highest level.
+//
+// # Safety
+//
+// `evaluator` must be `NULL` or a valid evaluator, `out` must be `NULL` or valid for writes.
+SifisTdStatus sifis_td_evaluator_risks(const SifisTdEvaluator *evaluator, SifisTdRisks *out);
+
+// Releases an array of risks, leaving it empty. Does nothing if `risks` is `NULL`.
+//
+// # Safety
+//
+// `risks` must be `NULL` or filled by `sifis_td_evaluator_risks`.
+void sifis_td_risks_free(SifisTdRisks *risks);
+
+// Returns a static description of a status.
+//
+// `status` is taken as an integer, so that any value coming from C is valid, and unknown codes
+// are described as an unknown status.
+const char *sifis_td_status_message(int status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SIFIS_TD_H */
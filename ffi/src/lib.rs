@@ -0,0 +1,546 @@
+//! C bindings to evaluate the SIFIS-Home hazards of Thing Descriptions.
+//!
+//! The library exposes an evaluator, created from the JSON of a Thing Description containing the
+//! `sho:` extension, which is fed with the state of the Thing and returns the active risks with
+//! the same semantics of [`IncrementalEvaluator`].
+//!
+//! The C declarations are in `include/sifis_td.h`, generated with `cbindgen --config
+//! cbindgen.toml --output include/sifis_td.h`.
+//!
+//! ```c
+//! SifisTdEvaluator *evaluator;
+//! SifisTdRisks risks;
+//!
+//! if (sifis_td_evaluator_new(td_json, "{\"properties\": {\"brightness\": 90}}", &evaluator)
+//!     != SIFIS_TD_STATUS_OK)
+//!     return -1;
+//!
+//! sifis_td_evaluator_risks(evaluator, &risks);
+//! for (size_t i = 0; i < risks.len; i++)
+//!     printf("%s: %u\n", risks.risks[i].id, risks.risks[i].level);
+//!
+//! sifis_td_risks_free(&risks);
+//! sifis_td_evaluator_free(evaluator);
+//! ```
+//!
+//! All the functions returning a [`SifisTdStatus`] leave their outputs untouched on error.
+
+#![warn(clippy::pedantic)]
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{error::Category, Value};
+use sifis_td::{
+    hazard,
+    incremental::{IncrementalEvaluator, PatchErrorKind, PatchOperation},
+    Sifis,
+};
+
+/// The result of a function.
+///
+/// The codes mirror the errors of the Rust library, and their values are stable.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SifisTdStatus {
+    /// No error.
+    Ok = 0,
+
+    /// A required pointer is `NULL`.
+    NullPointer = 1,
+
+    /// A string is not valid UTF-8.
+    InvalidUtf8 = 2,
+
+    /// A string is not valid JSON.
+    InvalidJson = 3,
+
+    /// A JSON document does not have the expected structure, i.e. a hazard with an unknown risk.
+    InvalidData = 4,
+
+    /// A JSON pointer of a JSON Patch is malformed.
+    PatchInvalidPointer = 5,
+
+    /// The value to change or to read with a JSON Patch does not exist.
+    PatchNotFound = 6,
+
+    /// A value of a JSON Patch cannot be placed in the target location.
+    PatchInvalidTarget = 7,
+
+    /// A JSON Patch moves a value into one of its children.
+    PatchMoveIntoChild = 8,
+
+    /// A `test` operation of a JSON Patch failed.
+    PatchTestFailed = 9,
+
+    /// An unexpected internal error.
+    Panic = 255,
+}
+
+impl SifisTdStatus {
+    const ALL: [Self; 11] = [
+        Self::Ok,
+        Self::NullPointer,
+        Self::InvalidUtf8,
+        Self::InvalidJson,
+        Self::InvalidData,
+        Self::PatchInvalidPointer,
+        Self::PatchNotFound,
+        Self::PatchInvalidTarget,
+        Self::PatchMoveIntoChild,
+        Self::PatchTestFailed,
+        Self::Panic,
+    ];
+
+    /// Returns the status with the given code, if any.
+    fn from_code(code: c_int) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|&status| status as c_int == code)
+    }
+
+    /// A static description of the status.
+    fn message(self) -> &'static CStr {
+        match self {
+            Self::Ok => c"no error",
+            Self::NullPointer => c"null pointer",
+            Self::InvalidUtf8 => c"invalid UTF-8 string",
+            Self::InvalidJson => c"invalid JSON",
+            Self::InvalidData => c"unexpected JSON structure",
+            Self::PatchInvalidPointer => c"malformed JSON pointer",
+            Self::PatchNotFound => c"value not found",
+            Self::PatchInvalidTarget => c"invalid target location",
+            Self::PatchMoveIntoChild => c"cannot move a value into one of its children",
+            Self::PatchTestFailed => c"test failed",
+            Self::Panic => c"internal error",
+        }
+    }
+}
+
+impl From<PatchErrorKind> for SifisTdStatus {
+    fn from(kind: PatchErrorKind) -> Self {
+        match kind {
+            PatchErrorKind::InvalidPointer => Self::PatchInvalidPointer,
+            PatchErrorKind::NotFound => Self::PatchNotFound,
+            PatchErrorKind::InvalidTarget => Self::PatchInvalidTarget,
+            PatchErrorKind::MoveIntoChild => Self::PatchMoveIntoChild,
+            PatchErrorKind::TestFailed => Self::PatchTestFailed,
+        }
+    }
+}
+
+/// An evaluator of the hazards of a Thing.
+pub struct SifisTdEvaluator(IncrementalEvaluator);
+
+/// An active risk.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SifisTdRisk {
+    /// The id of the risk, as in the Thing Description (i.e. `sho:FireHazard`).
+    pub id: *mut c_char,
+
+    /// The level of the risk.
+    pub level: u8,
+}
+
+/// An array of active risks, to be released with `sifis_td_risks_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SifisTdRisks {
+    /// The risks, `NULL` if there are none.
+    pub risks: *mut SifisTdRisk,
+
+    /// The number of risks.
+    pub len: usize,
+}
+
+/// Creates an evaluator for the hazards of a Thing Description.
+///
+/// `td` is the JSON of the Thing Description, or of an object with its `sho:risks` and
+/// `sho:hazards` only. `state` is the JSON of the initial state, with the same structure of the
+/// Thing Description (i.e. `{"properties": {"brightness": 90}}`), or `NULL` for an empty state.
+///
+/// On success, the evaluator is stored in `out`, and it must be released with
+/// `sifis_td_evaluator_free`.
+///
+/// # Safety
+///
+/// `td` and `state` must be `NULL` or valid nul-terminated strings, `out` must be `NULL` or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_evaluator_new(
+    td: *const c_char,
+    state: *const c_char,
+    out: *mut *mut SifisTdEvaluator,
+) -> SifisTdStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(SifisTdStatus::NullPointer);
+        }
+        let sifis: Sifis = parse_json(td)?;
+        let state = if state.is_null() {
+            Value::Object(serde_json::Map::new())
+        } else {
+            parse_json(state)?
+        };
+
+        let evaluator = SifisTdEvaluator(IncrementalEvaluator::new(&sifis, state));
+        out.write(Box::into_raw(Box::new(evaluator)));
+        Ok(())
+    })
+}
+
+/// Releases an evaluator. Does nothing if `evaluator` is `NULL`.
+///
+/// # Safety
+///
+/// `evaluator` must be `NULL` or returned by `sifis_td_evaluator_new`, and it must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_evaluator_free(evaluator: *mut SifisTdEvaluator) {
+    if !evaluator.is_null() {
+        drop(Box::from_raw(evaluator));
+    }
+}
+
+/// Replaces the state of a Thing with a JSON document.
+///
+/// # Safety
+///
+/// `evaluator` must be `NULL` or a valid evaluator, `state` must be `NULL` or a valid
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_evaluator_set_state(
+    evaluator: *mut SifisTdEvaluator,
+    state: *const c_char,
+) -> SifisTdStatus {
+    guard(|| {
+        let evaluator = evaluator.as_mut().ok_or(SifisTdStatus::NullPointer)?;
+        let state = parse_json(state)?;
+        evaluator.0.replace_state(state);
+        Ok(())
+    })
+}
+
+/// Applies a JSON Patch (RFC 6902) to the state of a Thing.
+///
+/// If an operation fails none of the operations is applied, and its index is stored in
+/// `failed_operation` unless it is `NULL`.
+///
+/// # Safety
+///
+/// `evaluator` must be `NULL` or a valid evaluator, `patch` must be `NULL` or a valid
+/// nul-terminated string, `failed_operation` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_evaluator_apply_patch(
+    evaluator: *mut SifisTdEvaluator,
+    patch: *const c_char,
+    failed_operation: *mut usize,
+) -> SifisTdStatus {
+    guard(|| {
+        let evaluator = evaluator.as_mut().ok_or(SifisTdStatus::NullPointer)?;
+        let patch: Vec<PatchOperation> = parse_json(patch)?;
+        evaluator.0.apply_patch(&patch).map_err(|err| {
+            if let Some(failed_operation) = failed_operation.as_mut() {
+                *failed_operation = err.operation;
+            }
+            SifisTdStatus::from(err.kind)
+        })?;
+        Ok(())
+    })
+}
+
+/// Stores the risks triggered by the current state of a Thing in `out`, which must be released
+/// with `sifis_td_risks_free`.
+///
+/// Each risk is present once, with its highest level.
+///
+/// # Safety
+///
+/// `evaluator` must be `NULL` or a valid evaluator, `out` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_evaluator_risks(
+    evaluator: *const SifisTdEvaluator,
+    out: *mut SifisTdRisks,
+) -> SifisTdStatus {
+    guard(|| {
+        let evaluator = evaluator.as_ref().ok_or(SifisTdStatus::NullPointer)?;
+        if out.is_null() {
+            return Err(SifisTdStatus::NullPointer);
+        }
+
+        let risks: Box<[SifisTdRisk]> = evaluator
+            .0
+            .risks()
+            .into_iter()
+            .map(|risk| SifisTdRisk {
+                id: id_string(risk.id).into_raw(),
+                level: risk.level,
+            })
+            .collect();
+        let len = risks.len();
+        let risks = if len == 0 {
+            ptr::null_mut()
+        } else {
+            Box::into_raw(risks).cast()
+        };
+
+        out.write(SifisTdRisks { risks, len });
+        Ok(())
+    })
+}
+
+/// Releases an array of risks, leaving it empty. Does nothing if `risks` is `NULL`.
+///
+/// # Safety
+///
+/// `risks` must be `NULL` or filled by `sifis_td_evaluator_risks`.
+#[no_mangle]
+pub unsafe extern "C" fn sifis_td_risks_free(risks: *mut SifisTdRisks) {
+    let Some(risks) = risks.as_mut() else {
+        return;
+    };
+
+    if !risks.risks.is_null() {
+        let array = Box::from_raw(ptr::slice_from_raw_parts_mut(risks.risks, risks.len));
+        for risk in &array {
+            drop(CString::from_raw(risk.id));
+        }
+    }
+
+    risks.risks = ptr::null_mut();
+    risks.len = 0;
+}
+
+/// Returns a static description of a status.
+///
+/// `status` is taken as an integer, so that any value coming from C is valid, and unknown codes
+/// are described as an unknown status.
+#[no_mangle]
+pub extern "C" fn sifis_td_status_message(status: c_int) -> *const c_char {
+    SifisTdStatus::from_code(status)
+        .map_or(c"unknown status", SifisTdStatus::message)
+        .as_ptr()
+}
+
+/// Runs the body of a function, converting a panic into [`SifisTdStatus::Panic`].
+fn guard(f: impl FnOnce() -> Result<(), SifisTdStatus>) -> SifisTdStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => SifisTdStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => SifisTdStatus::Panic,
+    }
+}
+
+/// Deserializes a nul-terminated JSON string.
+unsafe fn parse_json<T: DeserializeOwned>(json: *const c_char) -> Result<T, SifisTdStatus> {
+    if json.is_null() {
+        return Err(SifisTdStatus::NullPointer);
+    }
+    let json = CStr::from_ptr(json)
+        .to_str()
+        .map_err(|_| SifisTdStatus::InvalidUtf8)?;
+
+    serde_json::from_str(json).map_err(|err| match err.classify() {
+        Category::Data => SifisTdStatus::InvalidData,
+        Category::Io | Category::Syntax | Category::Eof => SifisTdStatus::InvalidJson,
+    })
+}
+
+fn id_string(id: hazard::Id) -> CString {
+    CString::new(id.as_str()).expect("hazard ids do not contain nul characters")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TD: &CStr = cr#"{
+        "title": "Lamp",
+        "sho:hazards": [{
+            "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+            "sho:conditions": [[{
+                "sho:pointer": "/properties/brightness",
+                "sho:condition": { "sho:value": 80, "sho:op": "ge" }
+            }]]
+        }]
+    }"#;
+
+    fn evaluator(state: Option<&CStr>) -> *mut SifisTdEvaluator {
+        let mut evaluator = ptr::null_mut();
+        let state = state.map_or(ptr::null(), CStr::as_ptr);
+        assert_eq!(
+            unsafe { sifis_td_evaluator_new(TD.as_ptr(), state, &raw mut evaluator) },
+            SifisTdStatus::Ok,
+        );
+        evaluator
+    }
+
+    fn risks(evaluator: *const SifisTdEvaluator) -> Vec<(String, u8)> {
+        let mut risks = SifisTdRisks {
+            risks: ptr::null_mut(),
+            len: 0,
+        };
+        unsafe {
+            assert_eq!(
+                sifis_td_evaluator_risks(evaluator, &raw mut risks),
+                SifisTdStatus::Ok
+            );
+            let out = (0..risks.len)
+                .map(|index| {
+                    let risk = &*risks.risks.add(index);
+                    let id = CStr::from_ptr(risk.id).to_str().unwrap().to_owned();
+                    (id, risk.level)
+                })
+                .collect();
+            sifis_td_risks_free(&raw mut risks);
+            assert!(risks.risks.is_null());
+            out
+        }
+    }
+
+    #[test]
+    fn evaluate() {
+        let evaluator = evaluator(None);
+        assert_eq!(risks(evaluator), []);
+
+        unsafe {
+            assert_eq!(
+                sifis_td_evaluator_set_state(
+                    evaluator,
+                    cr#"{"properties": {"brightness": 90}}"#.as_ptr()
+                ),
+                SifisTdStatus::Ok,
+            );
+            assert_eq!(risks(evaluator), [("sho:FireHazard".to_owned(), 7)]);
+
+            let mut failed = usize::MAX;
+            assert_eq!(
+                sifis_td_evaluator_apply_patch(
+                    evaluator,
+                    cr#"[{"op": "replace", "path": "/properties/brightness", "value": 10}]"#
+                        .as_ptr(),
+                    &raw mut failed,
+                ),
+                SifisTdStatus::Ok,
+            );
+            assert_eq!(failed, usize::MAX);
+            assert_eq!(risks(evaluator), []);
+
+            sifis_td_evaluator_free(evaluator);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let evaluator = evaluator(Some(cr#"{"properties": {"brightness": 90}}"#));
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                sifis_td_evaluator_new(ptr::null(), ptr::null(), &raw mut out),
+                SifisTdStatus::NullPointer,
+            );
+            assert_eq!(
+                sifis_td_evaluator_new(c"{".as_ptr(), ptr::null(), &raw mut out),
+                SifisTdStatus::InvalidJson,
+            );
+            assert_eq!(
+                sifis_td_evaluator_new(
+                    cr#"{"sho:hazards": [{"sho:risk": {"@id": "sho:Unknown"}}]}"#.as_ptr(),
+                    ptr::null(),
+                    &raw mut out,
+                ),
+                SifisTdStatus::InvalidData,
+            );
+            assert_eq!(
+                sifis_td_evaluator_set_state(evaluator, c"\xff".as_ptr()),
+                SifisTdStatus::InvalidUtf8,
+            );
+            assert!(out.is_null());
+
+            let mut failed = usize::MAX;
+            assert_eq!(
+                sifis_td_evaluator_apply_patch(
+                    evaluator,
+                    cr#"[
+                        {"op": "replace", "path": "/properties/brightness", "value": 10},
+                        {"op": "remove", "path": "/properties/color"}
+                    ]"#
+                    .as_ptr(),
+                    &raw mut failed,
+                ),
+                SifisTdStatus::PatchNotFound,
+            );
+            assert_eq!(failed, 1);
+            assert_eq!(risks(evaluator), [("sho:FireHazard".to_owned(), 7)]);
+
+            assert_eq!(
+                sifis_td_evaluator_risks(ptr::null(), ptr::null_mut()),
+                SifisTdStatus::NullPointer,
+            );
+            assert_eq!(
+                CStr::from_ptr(sifis_td_status_message(
+                    SifisTdStatus::PatchNotFound as c_int
+                )),
+                c"value not found",
+            );
+            assert_eq!(
+                CStr::from_ptr(sifis_td_status_message(SifisTdStatus::Panic as c_int)),
+                c"internal error",
+            );
+            for invalid in [42, -1, c_int::MAX] {
+                assert_eq!(
+                    CStr::from_ptr(sifis_td_status_message(invalid)),
+                    c"unknown status",
+                );
+            }
+
+            sifis_td_evaluator_free(evaluator);
+        }
+    }
+
+    /// Checks that the header declares every function and status of the library.
+    #[test]
+    fn header() {
+        let header = include_str!("../include/sifis_td.h");
+
+        for line in include_str!("lib.rs").lines() {
+            let Some(declaration) = line
+                .strip_prefix("pub unsafe extern \"C\" fn ")
+                .or_else(|| line.strip_prefix("pub extern \"C\" fn "))
+            else {
+                continue;
+            };
+            let name = &declaration[..declaration.find('(').unwrap()];
+            assert!(
+                header.contains(&format!("{name}(")),
+                "{name} is not declared"
+            );
+        }
+
+        let statuses = [
+            (SifisTdStatus::Ok, "OK"),
+            (SifisTdStatus::NullPointer, "NULL_POINTER"),
+            (SifisTdStatus::InvalidUtf8, "INVALID_UTF8"),
+            (SifisTdStatus::InvalidJson, "INVALID_JSON"),
+            (SifisTdStatus::InvalidData, "INVALID_DATA"),
+            (SifisTdStatus::PatchInvalidPointer, "PATCH_INVALID_POINTER"),
+            (SifisTdStatus::PatchNotFound, "PATCH_NOT_FOUND"),
+            (SifisTdStatus::PatchInvalidTarget, "PATCH_INVALID_TARGET"),
+            (SifisTdStatus::PatchMoveIntoChild, "PATCH_MOVE_INTO_CHILD"),
+            (SifisTdStatus::PatchTestFailed, "PATCH_TEST_FAILED"),
+            (SifisTdStatus::Panic, "PANIC"),
+        ];
+        assert_eq!(statuses.map(|(status, _)| status), SifisTdStatus::ALL);
+
+        for (status, name) in statuses {
+            let constant = format!("SIFIS_TD_STATUS_{name} = {},", status as u8);
+            assert!(header.contains(&constant), "{constant} is not declared");
+        }
+    }
+}
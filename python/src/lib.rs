@@ -0,0 +1,463 @@
+//! Python bindings to analyze the SIFIS-Home hazards of Thing Descriptions.
+//!
+//! The `sifis_td` module is built with [maturin](https://www.maturin.rs), and it exposes the
+//! parsing, the evaluation and the static analysis of the hazards, together with the catalog of
+//! the risks:
+//!
+//! ```python
+//! import json
+//! import sifis_td
+//!
+//! sifis = sifis_td.Sifis(open("lamp.td.json").read())
+//! for hazard in sifis.hazards:
+//!     print(hazard.risk.id.name, hazard.risk.level, hazard.conditions)
+//! print(sifis.evaluate({"properties": {"brightness": 90}}))
+//! print([issue.message for issue in sifis.analyze()])
+//! print([detail.name for detail in sifis_td.catalog()])
+//! ```
+//!
+//! The Thing Descriptions and the states can be passed either as JSON strings or as Python
+//! objects that can be converted to JSON, like dictionaries.
+
+#![warn(clippy::pedantic)]
+// The methods of Python classes always take `self` by reference.
+#![allow(clippy::trivially_copy_pass_by_ref)]
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyString};
+use serde_json::Value;
+use sifis_td::{
+    analysis::{self, Domains},
+    hazard, risk,
+};
+use wot_td::Thing;
+
+/// The id of a hazard, i.e. `sho:FireHazard`.
+#[pyclass(frozen, skip_from_py_object, eq, hash, module = "sifis_td")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct HazardId(hazard::Id);
+
+#[pymethods]
+impl HazardId {
+    /// Parses an id, i.e. `sho:FireHazard`.
+    #[staticmethod]
+    fn parse(id: &str) -> PyResult<Self> {
+        id.parse()
+            .map(Self)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// The id as in the Thing Descriptions.
+    #[getter]
+    fn id(&self) -> &'static str {
+        self.0.as_str()
+    }
+
+    /// The name of the hazard from the catalog.
+    #[getter]
+    fn name(&self) -> String {
+        self.0.detail().name.into_owned()
+    }
+
+    /// The category of the hazard, i.e. `Safety`.
+    #[getter]
+    fn category(&self) -> String {
+        self.0.category().to_string()
+    }
+
+    /// The details of the hazard from the catalog.
+    #[getter]
+    fn detail(&self) -> RiskDetail {
+        RiskDetail(self.0.detail())
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.0.as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HazardId('{}')", self.id())
+    }
+}
+
+/// The details of a risk.
+#[pyclass(frozen, skip_from_py_object, module = "sifis_td")]
+#[derive(Debug, Clone)]
+struct RiskDetail(risk::Detail);
+
+#[pymethods]
+impl RiskDetail {
+    /// The id of the risk.
+    #[getter]
+    fn id(&self) -> HazardId {
+        HazardId(self.0.id())
+    }
+
+    /// The name of the risk.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// The description of the risk.
+    #[getter]
+    fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    /// The category of the risk, i.e. `Safety`.
+    #[getter]
+    fn category(&self) -> String {
+        self.0.category().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RiskDetail('{}')", self.0.id().as_str())
+    }
+}
+
+/// A risk with its level.
+#[pyclass(frozen, skip_from_py_object, eq, hash, module = "sifis_td")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Risk(hazard::Risk);
+
+#[pymethods]
+impl Risk {
+    /// The id of the risk.
+    #[getter]
+    fn id(&self) -> HazardId {
+        HazardId(self.0.id)
+    }
+
+    /// The level of the risk.
+    #[getter]
+    fn level(&self) -> u8 {
+        self.0.level
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Risk('{}', {})", self.0.id.as_str(), self.0.level)
+    }
+}
+
+/// A hazard of a Thing.
+#[pyclass(frozen, skip_from_py_object, module = "sifis_td")]
+#[derive(Debug, Clone)]
+struct Hazard(hazard::Hazard);
+
+#[pymethods]
+impl Hazard {
+    /// The risk of the hazard.
+    #[getter]
+    fn risk(&self) -> Risk {
+        Risk(self.0.risk)
+    }
+
+    /// The conditions triggering the hazard, as alternative lists of conditions that must be
+    /// satisfied together, i.e. `[["/properties/brightness >= 80"]]`.
+    #[getter]
+    fn conditions(&self) -> Vec<Vec<String>> {
+        self.0
+            .conditions
+            .iter()
+            .map(|conditions| conditions.iter().map(ToString::to_string).collect())
+            .collect()
+    }
+
+    /// Checks whether a state of the Thing triggers the hazard.
+    fn is_triggered_by(&self, state: &Bound<'_, PyAny>) -> PyResult<bool> {
+        Ok(self.0.is_triggered_by(&to_json(state)?))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Hazard('{}', {})",
+            self.0.risk.id.as_str(),
+            self.0.risk.level
+        )
+    }
+}
+
+/// An issue found analyzing the conditions of the hazards.
+#[pyclass(frozen, skip_from_py_object, module = "sifis_td")]
+#[derive(Debug, Clone, Copy)]
+struct Issue(analysis::Issue);
+
+#[pymethods]
+impl Issue {
+    /// The kind of the issue: `unsatisfiable`, `unreachable`, `tautology`, `subsumed` or
+    /// `overlap`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.0 {
+            analysis::Issue::Unsatisfiable { .. } => "unsatisfiable",
+            analysis::Issue::Unreachable { .. } => "unreachable",
+            analysis::Issue::Tautology { .. } => "tautology",
+            analysis::Issue::Subsumed { .. } => "subsumed",
+            analysis::Issue::Overlap { .. } => "overlap",
+        }
+    }
+
+    /// The index of the hazard, or of the first hazard for overlaps.
+    #[getter]
+    fn hazard(&self) -> usize {
+        match self.0 {
+            analysis::Issue::Unsatisfiable { hazard, .. }
+            | analysis::Issue::Unreachable { hazard }
+            | analysis::Issue::Tautology { hazard, .. }
+            | analysis::Issue::Subsumed { hazard, .. } => hazard,
+            analysis::Issue::Overlap { first, .. } => first.hazard,
+        }
+    }
+
+    /// The index of the branch, or of the branch of the first hazard for overlaps, `None` for
+    /// unreachable hazards.
+    #[getter]
+    fn branch(&self) -> Option<usize> {
+        match self.0 {
+            analysis::Issue::Unsatisfiable { branch, .. }
+            | analysis::Issue::Tautology { branch, .. }
+            | analysis::Issue::Subsumed { branch, .. } => Some(branch),
+            analysis::Issue::Overlap { first, .. } => Some(first.branch),
+            analysis::Issue::Unreachable { .. } => None,
+        }
+    }
+
+    /// The description of the issue.
+    #[getter]
+    fn message(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Issue('{}')", self.0)
+    }
+}
+
+/// The hazards of a Thing Description.
+#[pyclass(frozen, skip_from_py_object, module = "sifis_td")]
+#[derive(Debug, Clone)]
+struct Sifis {
+    sifis: sifis_td::Sifis,
+    domains: Domains,
+}
+
+#[pymethods]
+impl Sifis {
+    /// Parses a Thing Description, or an object with its `sho:risks` and `sho:hazards` only.
+    #[new]
+    fn new(td: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let value = to_json(td)?;
+        let sifis = serde_json::from_value(value.clone()).map_err(value_error)?;
+
+        // The data types of the affordances are only known for complete Thing Descriptions.
+        let domains = serde_json::from_value::<Thing<sifis_td::Sifis>>(value)
+            .map(|thing| Domains::from_thing(&thing))
+            .unwrap_or_default();
+
+        Ok(Self { sifis, domains })
+    }
+
+    /// The risks declared by the Thing.
+    #[getter]
+    fn risks(&self) -> Vec<RiskDetail> {
+        self.sifis.risks.iter().cloned().map(RiskDetail).collect()
+    }
+
+    /// The hazards of the Thing.
+    #[getter]
+    fn hazards(&self) -> Vec<Hazard> {
+        self.sifis.hazards.iter().cloned().map(Hazard).collect()
+    }
+
+    /// Returns the risks triggered by a state of the Thing, each one with its highest level.
+    fn evaluate(&self, state: &Bound<'_, PyAny>) -> PyResult<Vec<Risk>> {
+        let risks = self.sifis.evaluate(&to_json(state)?);
+        Ok(risks.into_iter().map(Risk).collect())
+    }
+
+    /// Analyzes the conditions of the hazards, using the data schemas of the affordances when
+    /// the Thing Description is complete.
+    fn analyze(&self) -> Vec<Issue> {
+        self.sifis
+            .analyze_with(&self.domains)
+            .into_iter()
+            .map(Issue)
+            .collect()
+    }
+
+    /// Serializes the hazards as JSON.
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.sifis).expect("the hazards are serializable")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Sifis({} hazards)", self.sifis.hazards.len())
+    }
+}
+
+/// Returns all the risks of the catalog.
+#[pyfunction]
+fn catalog() -> Vec<RiskDetail> {
+    risk::ALL.iter().cloned().map(RiskDetail).collect()
+}
+
+/// Analysis of the SIFIS-Home hazards of Thing Descriptions.
+#[pymodule]
+#[pyo3(name = "sifis_td")]
+fn sifis_td_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<HazardId>()?;
+    module.add_class::<RiskDetail>()?;
+    module.add_class::<Risk>()?;
+    module.add_class::<Hazard>()?;
+    module.add_class::<Issue>()?;
+    module.add_class::<Sifis>()?;
+    module.add_function(wrap_pyfunction!(catalog, module)?)?;
+    Ok(())
+}
+
+/// Converts a JSON string, or a Python object using the `json` module.
+fn to_json(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json = match object.cast::<PyString>() {
+        Ok(string) => string.to_cow()?.into_owned(),
+        Err(_) => object
+            .py()
+            .import("json")?
+            .call_method1("dumps", (object,))?
+            .extract()?,
+    };
+
+    serde_json::from_str(&json).map_err(value_error)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn value_error(err: serde_json::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    /// Runs Python code with the module imported as `sifis_td`.
+    fn run(code: &str) {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "sifis_td").unwrap();
+            sifis_td_py(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("sifis_td", module).unwrap();
+
+            let code = CString::new(code).unwrap();
+            if let Err(err) = py.run(&code, Some(&globals), None) {
+                panic!("{err}");
+            }
+        });
+    }
+
+    #[test]
+    fn evaluate() {
+        run(r#"
+td = {
+    "@context": "https://www.w3.org/2022/wot/td/v1.1",
+    "title": "Lamp",
+    "security": "nosec",
+    "securityDefinitions": {"nosec": {"scheme": "nosec"}},
+    "properties": {
+        "brightness": {
+            "type": "integer",
+            "minimum": 0,
+            "maximum": 100,
+            "forms": [{"href": "/brightness"}],
+        },
+    },
+    "sho:risks": [{
+        "@id": "sho:FireHazard",
+        "sho:category": "sho:Safety",
+        "sho:description": "The lamp may set curtains on fire",
+        "sho:name": "Fire",
+    }],
+    "sho:hazards": [
+        {
+            "sho:risk": {"@id": "sho:FireHazard", "sho:level": 7},
+            "sho:conditions": [[{
+                "sho:pointer": "/properties/brightness",
+                "sho:condition": {"sho:value": 80, "sho:op": "ge"},
+            }]],
+        },
+        {
+            "sho:risk": {"@id": "sho:Burn", "sho:level": 2},
+            "sho:conditions": [[{
+                "sho:pointer": "/properties/brightness",
+                "sho:condition": {"sho:value": 120, "sho:op": "ge"},
+            }]],
+        },
+    ],
+}
+
+sifis = sifis_td.Sifis(td)
+assert repr(sifis) == "Sifis(2 hazards)"
+assert [detail.description for detail in sifis.risks] == ["The lamp may set curtains on fire"]
+
+hazard = sifis.hazards[0]
+assert hazard.risk.id == sifis_td.HazardId.parse("sho:FireHazard")
+assert hazard.risk.level == 7
+assert hazard.conditions == [["/properties/brightness >= 80"]]
+assert hazard.is_triggered_by('{"properties": {"brightness": 80}}')
+
+risks = sifis.evaluate({"properties": {"brightness": 90}})
+assert risks == [sifis.hazards[0].risk]
+assert str(risks[0].id) == "sho:FireHazard"
+assert risks[0].id.name == "Fire hazard"
+assert risks[0].id.category == "Safety"
+assert sifis.evaluate("{}") == []
+
+issues = sifis.analyze()
+assert [(issue.kind, issue.hazard, issue.branch) for issue in issues] == [
+    ("unsatisfiable", 1, 0),
+    ("unreachable", 1, None),
+]
+assert issues[1].message == "hazard #1: the hazard is never triggered"
+
+assert sifis_td.Sifis(sifis.to_json()).analyze() == []
+"#);
+    }
+
+    #[test]
+    fn catalog() {
+        run(r#"
+catalog = sifis_td.catalog()
+assert len(catalog) == 24
+assert catalog[0].id.id == "sho:AirPoisoning"
+assert catalog[0].name == "Air poisoning"
+assert catalog[0].description == "The execution may release toxic gases"
+assert len({detail.id for detail in catalog}) == len(catalog)
+
+fire = sifis_td.HazardId.parse("sho:FireHazard")
+assert fire.detail.name == "Fire hazard"
+assert repr(fire) == "HazardId('sho:FireHazard')"
+"#);
+    }
+
+    #[test]
+    fn errors() {
+        run(r#"
+for invalid in ['{"sho:hazards": [', {"sho:hazards": 3}]:
+    try:
+        sifis_td.Sifis(invalid)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError(invalid)
+
+try:
+    sifis_td.HazardId.parse("sho:Unknown")
+except ValueError as err:
+    assert str(err) == "unknown hazard id sho:Unknown"
+else:
+    raise AssertionError
+"#);
+    }
+}
@@ -0,0 +1,735 @@
+//! Static analysis of hazard conditions.
+//!
+//! The conditions of a hazard are a logic `OR` of branches, each one being a logic `AND` of
+//! conditions. Some mistakes can be made writing them, and they are not easy to spot by eye:
+//!
+//! - a branch like `x < 3 AND x > 5` can never be satisfied;
+//! - a branch without conditions, or with conditions covering every possible value, is always
+//!   satisfied;
+//! - a branch implying another branch of the same hazard is redundant;
+//! - two hazards with the same [`Id`] but different levels can be triggered by the same state,
+//!   making the level of the risk ambiguous.
+//!
+//! [`Sifis::analyze`] reports all these issues. The values that the data can assume can be
+//! restricted using the data schemas of the Thing, see [`Domains::from_thing`] and
+//! [`Sifis::analyze_with`].
+//!
+//! ```
+//! use sifis_td::{analysis::Issue, Sifis};
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| {
+//!         cond.when("/properties/temperature")
+//!             .lt(3)
+//!             .and("/properties/temperature")
+//!             .gt(5)
+//!     })
+//!     .build();
+//!
+//! assert_eq!(
+//!     sifis.analyze(),
+//!     [
+//!         Issue::Unsatisfiable {
+//!             hazard: 0,
+//!             branch: 0,
+//!         },
+//!         Issue::Unreachable { hazard: 0 },
+//!     ],
+//! );
+//! ```
+//!
+//! [`Id`]: hazard::Id
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    ops::{Bound, Not},
+};
+
+use serde::{Deserialize, Serialize};
+use wot_td::{
+    extend::ExtendableThing,
+    thing::{DataSchema, DataSchemaSubtype, Maximum, Minimum},
+    Thing,
+};
+
+use crate::{
+    condition::Value,
+    domain::{Branch, Domain, Kind, Range},
+    hazard, Sifis,
+};
+
+impl Sifis {
+    /// Analyzes the conditions of the hazards, assuming that the data can assume any value.
+    ///
+    /// See the [module documentation](crate::analysis) for more information.
+    #[inline]
+    #[must_use]
+    pub fn analyze(&self) -> Vec<Issue> {
+        self.analyze_with(&Domains::default())
+    }
+
+    /// Analyzes the conditions of the hazards, restricting the data to the given domains.
+    ///
+    /// The issues are sorted by the index of the (first) hazard they refer to.
+    #[must_use]
+    pub fn analyze_with(&self, domains: &Domains) -> Vec<Issue> {
+        let hazards: Vec<Vec<Option<Branch>>> = self
+            .hazards
+            .iter()
+            .map(|hazard| {
+                hazard
+                    .conditions
+                    .iter()
+                    .map(|conditions| domains.restrict(Branch::from_conditions(conditions)?))
+                    .collect()
+            })
+            .collect();
+
+        let mut issues = Vec::new();
+        for (hazard_index, branches) in hazards.iter().enumerate() {
+            for (branch_index, branch) in branches.iter().enumerate() {
+                let Some(branch) = branch else {
+                    issues.push(Issue::Unsatisfiable {
+                        hazard: hazard_index,
+                        branch: branch_index,
+                    });
+                    continue;
+                };
+
+                if domains.is_tautology(branch) {
+                    issues.push(Issue::Tautology {
+                        hazard: hazard_index,
+                        branch: branch_index,
+                    });
+                }
+
+                // Equivalent branches are reported only once, the latter subsumed by the former.
+                let subsumed_by = branches.iter().enumerate().find_map(|(index, other)| {
+                    let other = other.as_ref()?;
+                    (index != branch_index
+                        && domains.implies(branch, other)
+                        && (index < branch_index || domains.implies(other, branch).not()))
+                    .then_some(index)
+                });
+                if let Some(by) = subsumed_by {
+                    issues.push(Issue::Subsumed {
+                        hazard: hazard_index,
+                        branch: branch_index,
+                        by,
+                    });
+                }
+            }
+
+            if branches.iter().all(Option::is_none) {
+                issues.push(Issue::Unreachable {
+                    hazard: hazard_index,
+                });
+            }
+
+            let hazard = &self.hazards[hazard_index];
+            for (other_index, other) in self.hazards.iter().enumerate().skip(hazard_index + 1) {
+                if other.risk.id != hazard.risk.id || other.risk.level == hazard.risk.level {
+                    continue;
+                }
+
+                let overlapping = branches.iter().enumerate().find_map(|(index, branch)| {
+                    let branch = branch.as_ref()?;
+                    hazards[other_index]
+                        .iter()
+                        .position(|other| {
+                            other
+                                .as_ref()
+                                .is_some_and(|other| domains.overlap(branch, other))
+                        })
+                        .map(|other| (index, other))
+                });
+
+                if let Some((branch, other_branch)) = overlapping {
+                    issues.push(Issue::Overlap {
+                        id: hazard.risk.id,
+                        first: Location {
+                            hazard: hazard_index,
+                            branch,
+                            level: hazard.risk.level,
+                        },
+                        second: Location {
+                            hazard: other_index,
+                            branch: other_branch,
+                            level: other.risk.level,
+                        },
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+/// An issue found analyzing the conditions of the hazards.
+///
+/// Hazards and branches are identified by their index in [`Sifis::hazards`] and
+/// [`Hazard::conditions`], respectively.
+///
+/// [`Hazard::conditions`]: hazard::Hazard::conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Issue {
+    /// The conditions of the branch can never be satisfied at the same time.
+    Unsatisfiable {
+        /// The index of the hazard.
+        hazard: usize,
+
+        /// The index of the branch.
+        branch: usize,
+    },
+
+    /// None of the branches of the hazard can be satisfied, therefore the hazard is never
+    /// triggered.
+    Unreachable {
+        /// The index of the hazard.
+        hazard: usize,
+    },
+
+    /// The conditions of the branch are always satisfied, therefore the hazard is always
+    /// triggered.
+    Tautology {
+        /// The index of the hazard.
+        hazard: usize,
+
+        /// The index of the branch.
+        branch: usize,
+    },
+
+    /// The branch is redundant, because it is satisfied only when another branch of the same
+    /// hazard is satisfied too.
+    Subsumed {
+        /// The index of the hazard.
+        hazard: usize,
+
+        /// The index of the redundant branch.
+        branch: usize,
+
+        /// The index of the branch that makes the other redundant.
+        by: usize,
+    },
+
+    /// Two hazards with the same id and different levels can be triggered at the same time.
+    Overlap {
+        /// The id shared by the hazards.
+        id: hazard::Id,
+
+        /// The first of the hazards.
+        first: Location,
+
+        /// The second of the hazards.
+        second: Location,
+    },
+}
+
+/// A branch of a hazard, together with the level of its risk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Location {
+    /// The index of the hazard.
+    pub hazard: usize,
+
+    /// The index of the branch.
+    pub branch: usize,
+
+    /// The level of the risk of the hazard.
+    pub level: u8,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsatisfiable { hazard, branch } => write!(
+                f,
+                "hazard #{hazard}, branch #{branch}: the conditions can never be satisfied",
+            ),
+            Self::Unreachable { hazard } => {
+                write!(f, "hazard #{hazard}: the hazard is never triggered")
+            }
+            Self::Tautology { hazard, branch } => write!(
+                f,
+                "hazard #{hazard}, branch #{branch}: the conditions are always satisfied",
+            ),
+            Self::Subsumed { hazard, branch, by } => write!(
+                f,
+                "hazard #{hazard}, branch #{branch}: the branch is redundant with branch #{by}",
+            ),
+            Self::Overlap { id, first, second } => write!(
+                f,
+                "hazards #{} (branch #{}, level {}) and #{} (branch #{}, level {}): {:?} can be \
+                 triggered with different levels at the same time",
+                first.hazard,
+                first.branch,
+                first.level,
+                second.hazard,
+                second.branch,
+                second.level,
+                id,
+            ),
+        }
+    }
+}
+
+/// The values that the data behind each JSON pointer can assume.
+///
+/// JSON pointers without a domain can assume any value.
+#[derive(Debug, Clone, Default)]
+pub struct Domains(pub(crate) BTreeMap<hazard::JsonPointer, Domain>);
+
+impl Domains {
+    /// Extracts the domains from the data schemas of the affordances of a Thing.
+    ///
+    /// The JSON pointers follow the same structure of the Thing Description: the data of a
+    /// property `prop` is found in `/properties/prop`, the input of an action `act` in
+    /// `/actions/act` and the data of an event `ev` in `/events/ev`. The fields of object
+    /// schemas and the elements of tuple-like array schemas are walked recursively.
+    ///
+    /// The `minimum` and `maximum` of numeric schemas are used as bounds, the values of `const`
+    /// and `enum` restrict the allowed values.
+    #[must_use]
+    pub fn from_thing<Other>(thing: &Thing<Other>) -> Self
+    where
+        Other: ExtendableThing,
+    {
+        let mut domains = Self::default();
+        let mut tokens = Vec::new();
+
+        let mut walk = |kind: &str, name: &str, schema| {
+            tokens.clear();
+            tokens.extend([kind.to_owned(), name.to_owned()]);
+            domains.collect(schema, &mut tokens);
+        };
+
+        for (name, property) in thing.properties.iter().flatten() {
+            walk("properties", name, &property.data_schema);
+        }
+
+        for (name, action) in thing.actions.iter().flatten() {
+            if let Some(input) = &action.input {
+                walk("actions", name, input);
+            }
+        }
+
+        for (name, event) in thing.events.iter().flatten() {
+            if let Some(data) = &event.data {
+                walk("events", name, data);
+            }
+        }
+
+        domains
+    }
+
+    /// Returns `true` if there are no domains.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn collect<DS, AS, OS>(&mut self, schema: &DataSchema<DS, AS, OS>, tokens: &mut Vec<String>) {
+        match &schema.subtype {
+            Some(DataSchemaSubtype::Object(object)) => {
+                for (name, schema) in object.properties.iter().flatten() {
+                    tokens.push(name.clone());
+                    self.collect(schema, tokens);
+                    tokens.pop();
+                }
+            }
+            Some(DataSchemaSubtype::Array(array)) => {
+                // A single item schema applies to all the elements, which cannot be addressed.
+                let items = array.items.as_deref().unwrap_or_default();
+                if items.len() > 1 {
+                    for (index, schema) in items.iter().enumerate() {
+                        tokens.push(index.to_string());
+                        self.collect(schema, tokens);
+                        tokens.pop();
+                    }
+                }
+            }
+            _ => {
+                if let Some(domain) = domain_from_schema(schema) {
+                    let pointer = hazard::JsonPointer(jsonptr::Pointer::new(tokens));
+                    self.0.insert(pointer, domain);
+                }
+            }
+        }
+    }
+
+    /// Restricts the ranges of a branch to the domains, returning `None` if it is unsatisfiable.
+    pub(crate) fn restrict(&self, mut branch: Branch) -> Option<Branch> {
+        for (pointer, range) in &mut branch.0 {
+            if let Some(domain) = self.0.get(pointer) {
+                *range = domain.restrict(range)?;
+            }
+        }
+
+        Some(branch)
+    }
+
+    /// Checks whether a branch is satisfied by every value of the domains.
+    fn is_tautology(&self, branch: &Branch) -> bool {
+        branch.0.iter().all(|(pointer, range)| {
+            self.0
+                .get(pointer)
+                .is_some_and(|domain| domain.is_covered_by(range))
+        })
+    }
+
+    /// Checks whether every state satisfying `a` also satisfies `b`.
+    fn implies(&self, a: &Branch, b: &Branch) -> bool {
+        b.0.iter().all(|(pointer, range)| match a.0.get(pointer) {
+            Some(cur) => cur.is_subset(range),
+            None => self
+                .0
+                .get(pointer)
+                .is_some_and(|domain| domain.is_covered_by(range)),
+        })
+    }
+
+    /// Checks whether there is a state satisfying both the branches.
+    fn overlap(&self, a: &Branch, b: &Branch) -> bool {
+        a.0.iter().all(|(pointer, range)| {
+            let Some(other) = b.0.get(pointer) else {
+                return true;
+            };
+
+            range
+                .intersect(other)
+                .is_some_and(|range| match self.0.get(pointer) {
+                    Some(domain) => domain.restrict(&range).is_some(),
+                    None => true,
+                })
+        })
+    }
+}
+
+fn domain_from_schema<DS, AS, OS>(schema: &DataSchema<DS, AS, OS>) -> Option<Domain> {
+    let values: Option<Vec<Value>> = match (&schema.constant, &schema.enumeration) {
+        (Some(constant), _) => Some(vec![scalar_value(constant)?]),
+        (None, Some(values)) => values.iter().map(scalar_value).collect(),
+        (None, None) => None,
+    };
+
+    let (range, integer) = match &schema.subtype {
+        Some(DataSchemaSubtype::Boolean) => (Range::full(Kind::Bool), false),
+        Some(DataSchemaSubtype::String(_)) => (Range::full(Kind::String), false),
+        Some(DataSchemaSubtype::Number(number)) => {
+            let bound = |value: f64, inclusive: bool| {
+                Value::try_from(value).map_or(Bound::Unbounded, |value| {
+                    if inclusive {
+                        Bound::Included(value)
+                    } else {
+                        Bound::Excluded(value)
+                    }
+                })
+            };
+            let lower = match number.minimum {
+                Some(Minimum::Inclusive(value)) => bound(value, true),
+                Some(Minimum::Exclusive(value)) => bound(value, false),
+                None => Bound::Unbounded,
+            };
+            let upper = match number.maximum {
+                Some(Maximum::Inclusive(value)) => bound(value, true),
+                Some(Maximum::Exclusive(value)) => bound(value, false),
+                None => Bound::Unbounded,
+            };
+            (Range::new(Kind::Number, lower, upper)?, false)
+        }
+        Some(DataSchemaSubtype::Integer(integer)) => {
+            let lower = match integer.minimum {
+                Some(Minimum::Inclusive(value)) => Bound::Included(Value::from(value)),
+                Some(Minimum::Exclusive(value)) => Bound::Excluded(Value::from(value)),
+                None => Bound::Unbounded,
+            };
+            let upper = match integer.maximum {
+                Some(Maximum::Inclusive(value)) => Bound::Included(Value::from(value)),
+                Some(Maximum::Exclusive(value)) => Bound::Excluded(Value::from(value)),
+                None => Bound::Unbounded,
+            };
+            (Range::new(Kind::Number, lower, upper)?, true)
+        }
+        // Without a type, the domain can only be inferred by values of the same kind.
+        None => {
+            let values = values.as_ref()?;
+            let kind = Kind::of(values.first()?);
+            if values.iter().any(|value| Kind::of(value) != kind) {
+                return None;
+            }
+            (Range::full(kind), false)
+        }
+        Some(
+            DataSchemaSubtype::Array(_) | DataSchemaSubtype::Object(_) | DataSchemaSubtype::Null,
+        ) => return None,
+    };
+
+    Domain::new(range, integer, values)
+}
+
+/// Converts a JSON value to a [`Value`], if it is possible to use it in a condition.
+fn scalar_value(value: &serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Bool(value) => Some(Value::Bool(*value)),
+        serde_json::Value::Number(value) => Some(Value::Number(value.clone())),
+        serde_json::Value::String(value) => Some(Value::from(value.clone())),
+        serde_json::Value::Null | serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wot_td::builder::{
+        IntegerDataSchemaBuilderLike, NumberDataSchemaBuilderLike, ObjectDataSchemaBuilderLike,
+        SpecializableDataSchema,
+    };
+
+    use super::*;
+
+    #[test]
+    fn unsatisfiable_and_tautology() {
+        let mut sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/a")
+                    .lt(3)
+                    .and("/properties/a")
+                    .gt(5)
+                    .or(|cond| cond.when("/properties/b").eq(true))
+            })
+            .explosion(2, |cond| cond)
+            .build();
+        sifis.hazards.push(hazard::Hazard {
+            risk: hazard::Risk {
+                id: hazard::Id::Explosion,
+                level: 2,
+            },
+            conditions: vec![vec![]],
+        });
+
+        assert_eq!(
+            sifis.analyze(),
+            [
+                Issue::Unsatisfiable {
+                    hazard: 0,
+                    branch: 0,
+                },
+                Issue::Unreachable { hazard: 1 },
+                Issue::Tautology {
+                    hazard: 2,
+                    branch: 0,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn subsumed_branches() {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/a")
+                    .gt(5)
+                    .and("/properties/b")
+                    .eq(true)
+                    .or(|cond| cond.when("/properties/a").gt(3))
+                    .or(|cond| cond.when("/properties/a").ge(3).and("/properties/a").ne(3))
+            })
+            .build();
+
+        assert_eq!(
+            sifis.analyze(),
+            [
+                Issue::Subsumed {
+                    hazard: 0,
+                    branch: 0,
+                    by: 1,
+                },
+                Issue::Subsumed {
+                    hazard: 0,
+                    branch: 2,
+                    by: 1,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn overlapping_levels() {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/a").lt(10))
+            .fire_hazard(7, |cond| cond.when("/properties/a").ge(10))
+            .fire_hazard(5, |cond| {
+                cond.when("/properties/b")
+                    .eq(true)
+                    .or(|cond| cond.when("/properties/a").gt(20))
+            })
+            .explosion(1, |cond| cond.when("/properties/a").lt(10))
+            .fire_hazard(3, |cond| cond.when("/properties/a").lt(5))
+            .build();
+
+        let issues = sifis.analyze();
+        assert_eq!(
+            issues,
+            [
+                Issue::Overlap {
+                    id: hazard::Id::FireHazard,
+                    first: Location {
+                        hazard: 0,
+                        branch: 0,
+                        level: 3,
+                    },
+                    second: Location {
+                        hazard: 2,
+                        branch: 0,
+                        level: 5,
+                    },
+                },
+                Issue::Overlap {
+                    id: hazard::Id::FireHazard,
+                    first: Location {
+                        hazard: 1,
+                        branch: 0,
+                        level: 7,
+                    },
+                    second: Location {
+                        hazard: 2,
+                        branch: 0,
+                        level: 5,
+                    },
+                },
+                Issue::Overlap {
+                    id: hazard::Id::FireHazard,
+                    first: Location {
+                        hazard: 2,
+                        branch: 0,
+                        level: 5,
+                    },
+                    second: Location {
+                        hazard: 4,
+                        branch: 0,
+                        level: 3,
+                    },
+                },
+            ],
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "hazards #0 (branch #0, level 3) and #2 (branch #0, level 5): FireHazard can be \
+             triggered with different levels at the same time",
+        );
+    }
+
+    #[test]
+    fn thing_domains() {
+        let thing = Thing::builder("thing")
+            .ext(Sifis::default())
+            .finish_extend()
+            .property("prop", |b| {
+                b.ext(())
+                    .ext_interaction(())
+                    .ext_data_schema(())
+                    .finish_extend_data_schema()
+                    .object()
+                    .property("number", false, |b| {
+                        b.ext(()).finish_extend().number().minimum(0.).maximum(1.)
+                    })
+                    .property("integer", false, |b| {
+                        b.ext(()).finish_extend().integer().minimum(1).maximum(10)
+                    })
+            })
+            .action("toggle", |b| {
+                b.ext(())
+                    .ext_interaction(())
+                    .input(|b| b.ext(()).finish_extend().bool())
+            })
+            .build()
+            .unwrap();
+
+        let domains = Domains::from_thing(&thing);
+        assert_eq!(
+            domains
+                .0
+                .keys()
+                .map(|pointer| &**pointer)
+                .collect::<Vec<_>>(),
+            [
+                "/actions/toggle",
+                "/properties/prop/integer",
+                "/properties/prop/number",
+            ],
+        );
+
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/prop/number").gt(1))
+            .take_pictures(5, |cond| {
+                cond.when("/properties/prop/integer")
+                    .gt(3)
+                    .and("/properties/prop/integer")
+                    .lt(4)
+            })
+            .explosion(1, |cond| cond.when("/properties/prop/number").ge(0))
+            .explosion(2, |cond| {
+                cond.when("/actions/toggle")
+                    .ne(true)
+                    .or(|cond| cond.when("/actions/toggle").ne(false))
+            })
+            .build();
+
+        let overlap = Issue::Overlap {
+            id: hazard::Id::Explosion,
+            first: Location {
+                hazard: 2,
+                branch: 0,
+                level: 1,
+            },
+            second: Location {
+                hazard: 3,
+                branch: 0,
+                level: 2,
+            },
+        };
+        assert_eq!(sifis.analyze(), [overlap]);
+        assert_eq!(
+            sifis.analyze_with(&domains),
+            [
+                Issue::Unsatisfiable {
+                    hazard: 0,
+                    branch: 0,
+                },
+                Issue::Unreachable { hazard: 0 },
+                Issue::Unsatisfiable {
+                    hazard: 1,
+                    branch: 0,
+                },
+                Issue::Unreachable { hazard: 1 },
+                Issue::Tautology {
+                    hazard: 2,
+                    branch: 0,
+                },
+                overlap,
+            ],
+        );
+    }
+
+    #[test]
+    fn serialize_issue() {
+        assert_eq!(
+            serde_json::to_value(Issue::Subsumed {
+                hazard: 1,
+                branch: 2,
+                by: 0,
+            })
+            .unwrap(),
+            serde_json::json!({
+                "kind": "subsumed",
+                "hazard": 1,
+                "branch": 2,
+                "by": 0,
+            }),
+        );
+    }
+}
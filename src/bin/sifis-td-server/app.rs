@@ -0,0 +1,301 @@
+//! The routes of the service.
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sifis_td::{hazard, home::Home, incremental::RiskChanged};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+/// The number of transitions kept for slow subscribers of the event stream.
+const EVENTS_CAPACITY: usize = 256;
+
+/// The change of level of a risk of a Thing, sent to the subscribers of `/events`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Transition {
+    thing: String,
+    #[serde(flatten)]
+    change: RiskChanged,
+}
+
+struct AppState {
+    home: Mutex<Home<String>>,
+    transitions: broadcast::Sender<Transition>,
+}
+
+/// Creates the router of the service for the Things of a home.
+///
+/// - `GET /things` lists the ids of the Things;
+/// - `GET /things/{id}/risks` returns the risks of a Thing;
+/// - `POST /things/{id}/state` replaces the state of a Thing, returning the changed risks;
+/// - `GET /risks` returns the maximum level of each risk and the risks of each Thing;
+/// - `GET /events` streams the changed risks as server-sent events.
+pub(crate) fn router(home: Home<String>) -> Router {
+    let (transitions, _) = broadcast::channel(EVENTS_CAPACITY);
+    let state = Arc::new(AppState {
+        home: Mutex::new(home),
+        transitions,
+    });
+
+    Router::new()
+        .route("/things", get(things))
+        .route("/things/{id}/risks", get(thing_risks))
+        .route("/things/{id}/state", post(update_state))
+        .route("/risks", get(risks))
+        .route("/events", get(events))
+        .with_state(state)
+}
+
+impl AppState {
+    fn with_home<T>(&self, f: impl FnOnce(&mut Home<String>) -> T) -> T {
+        let mut home = self.home.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut home)
+    }
+}
+
+async fn things(State(state): State<Arc<AppState>>) -> Json<Vec<String>> {
+    Json(state.with_home(|home| home.iter().map(|(id, _)| id.clone()).collect()))
+}
+
+async fn thing_risks(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<hazard::Risk>>, Response> {
+    state
+        .with_home(|home| home.get(&id).map(|entry| entry.risks().to_vec()))
+        .map(Json)
+        .ok_or_else(|| unknown_thing(&id))
+}
+
+async fn update_state(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(thing_state): Json<Value>,
+) -> Result<Json<Vec<RiskChanged>>, Response> {
+    let changes = state
+        .with_home(|home| {
+            let before = home.get(&id)?.risks().to_vec();
+            let after = home.update_state(&id, thing_state)?;
+            let changes = RiskChanged::between(&before, after);
+
+            // Sent while holding the lock, so the events are in the same order as the updates.
+            for &change in &changes {
+                // No subscribers is not an error.
+                let _ = state.transitions.send(Transition {
+                    thing: id.clone(),
+                    change,
+                });
+            }
+
+            Some(changes)
+        })
+        .ok_or_else(|| unknown_thing(&id))?;
+
+    Ok(Json(changes))
+}
+
+/// The risks of the whole home.
+#[derive(Debug, Serialize)]
+struct HomeRisks {
+    /// The maximum level of each risk, see [`Home::max_risks`].
+    risks: Vec<hazard::Risk>,
+
+    /// The risks of each Thing.
+    things: BTreeMap<String, Vec<hazard::Risk>>,
+}
+
+async fn risks(State(state): State<Arc<AppState>>) -> Json<HomeRisks> {
+    Json(state.with_home(|home| {
+        HomeRisks {
+            risks: home.max_risks(),
+            things: home
+                .iter()
+                .map(|(id, entry)| (id.clone(), entry.risks().to_vec()))
+                .collect(),
+        }
+    }))
+}
+
+async fn events(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Subscribers lagging behind miss some transitions, but they still get the next ones.
+    let stream = BroadcastStream::new(state.transitions.subscribe()).filter_map(|transition| {
+        let transition = transition.ok()?;
+        Some(Ok(Event::default()
+            .event("transition")
+            .json_data(transition)
+            .expect("transitions are serializable")))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn unknown_thing(id: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": format!("unknown Thing {id}") })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, Bytes},
+        http::{header, Request},
+    };
+    use http_body_util::BodyExt;
+    use sifis_td::Sifis;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        let lamp = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(80))
+            .build();
+        let camera = Sifis::builder()
+            .take_pictures(5, |cond| cond.when("/properties/on").eq(true))
+            .build();
+
+        router(
+            [
+                ("lamp".to_owned(), lamp, json!({})),
+                (
+                    "camera".to_owned(),
+                    camera,
+                    json!({ "properties": { "on": true } }),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    fn post(uri: &str, body: &Value) -> Request<Body> {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn risks() {
+        let app = app();
+
+        assert_eq!(
+            send(&app, get("/things")).await,
+            (StatusCode::OK, json!(["camera", "lamp"])),
+        );
+        assert_eq!(
+            send(&app, get("/things/lamp/risks")).await,
+            (StatusCode::OK, json!([])),
+        );
+
+        let (status, changes) = send(
+            &app,
+            post(
+                "/things/lamp/state",
+                &json!({ "properties": { "brightness": 90 } }),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            changes,
+            json!([{ "id": "sho:FireHazard", "old_level": null, "new_level": 3 }]),
+        );
+
+        let fire = json!({ "@id": "sho:FireHazard", "sho:level": 3 });
+        let pictures = json!({ "@id": "sho:TakePictures", "sho:level": 5 });
+        assert_eq!(
+            send(&app, get("/things/lamp/risks")).await,
+            (StatusCode::OK, json!([fire])),
+        );
+        assert_eq!(
+            send(&app, get("/risks")).await,
+            (
+                StatusCode::OK,
+                json!({
+                    "risks": [pictures, fire],
+                    "things": { "camera": [pictures], "lamp": [fire] },
+                }),
+            ),
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_thing() {
+        let app = app();
+
+        assert_eq!(
+            send(&app, get("/things/oven/risks")).await,
+            (
+                StatusCode::NOT_FOUND,
+                json!({ "error": "unknown Thing oven" })
+            ),
+        );
+        assert_eq!(
+            send(&app, post("/things/oven/state", &json!({}))).await.0,
+            StatusCode::NOT_FOUND,
+        );
+    }
+
+    #[tokio::test]
+    async fn events() {
+        let app = app();
+
+        let response = app.clone().oneshot(get("/events")).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let mut body = response.into_body();
+
+        send(
+            &app,
+            post(
+                "/things/camera/state",
+                &json!({ "properties": { "on": false } }),
+            ),
+        )
+        .await;
+
+        let frame = body.frame().await.unwrap().unwrap();
+        assert_eq!(
+            frame.into_data().unwrap(),
+            Bytes::from(
+                "event: transition\n\
+                 data: {\"thing\":\"camera\",\"id\":\"sho:TakePictures\",\"old_level\":5,\
+                 \"new_level\":null}\n\n"
+            ),
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Local HTTP service evaluating the SIFIS-Home hazards of a set of Thing Descriptions.
+//!
+//! The Thing Descriptions are loaded from the `*.json` files of a directory, and each Thing is
+//! identified by the name of its file without the extension (i.e.: `lamp` for `lamp.json`). The
+//! state of each Thing is initially empty, and it is replaced through `POST /things/{id}/state`
+//! with a JSON document with the same structure of the Thing Description (i.e.:
+//! `{"properties": {"brightness": 90}}`).
+//!
+//! The risks are served as JSON by `GET /things/{id}/risks` and `GET /risks`, and their changes
+//! are streamed as server-sent events by `GET /events`.
+
+#![warn(clippy::pedantic)]
+
+mod app;
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::Parser;
+use serde_json::Value;
+use sifis_td::{home::Home, Sifis};
+use wot_td::Thing;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// The directory containing the Thing Descriptions.
+    dir: PathBuf,
+
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let home = match load(&cli.dir) {
+        Ok(home) => home,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let listener = match tokio::net::TcpListener::bind(cli.addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("error: cannot listen on {}: {err}", cli.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    eprintln!(
+        "serving {} Thing(s) from {} on http://{}",
+        home.len(),
+        cli.dir.display(),
+        cli.addr,
+    );
+    match axum::serve(listener, app::router(home)).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads the Thing Descriptions of a directory, with an empty state.
+fn load(dir: &Path) -> Result<Home<String>, LoadError> {
+    let error = |path: &Path, kind| LoadError {
+        path: path.to_owned(),
+        kind,
+    };
+
+    let mut paths = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|err| error(dir, LoadErrorKind::Io(err)))?;
+    paths.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+
+    let mut home = Home::new();
+    for path in paths {
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            return Err(error(&path, LoadErrorKind::InvalidName));
+        };
+        let data = fs::read(&path).map_err(|err| error(&path, LoadErrorKind::Io(err)))?;
+        let thing: Thing<Sifis> =
+            serde_json::from_slice(&data).map_err(|err| error(&path, LoadErrorKind::Json(err)))?;
+
+        home.insert(
+            id.to_owned(),
+            thing.other,
+            Value::Object(serde_json::Map::new()),
+        );
+    }
+
+    Ok(home)
+}
+
+/// An error loading a Thing Description.
+#[derive(Debug)]
+struct LoadError {
+    path: PathBuf,
+    kind: LoadErrorKind,
+}
+
+#[derive(Debug)]
+enum LoadErrorKind {
+    Io(io::Error),
+    Json(serde_json::Error),
+    InvalidName,
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.kind {
+            LoadErrorKind::Io(err) => write!(f, "cannot read {path}: {err}"),
+            LoadErrorKind::Json(err) => write!(f, "invalid {path}: {err}"),
+            LoadErrorKind::InvalidName => write!(f, "the name of {path} is not valid UTF-8"),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Io(err) => Some(err),
+            LoadErrorKind::Json(err) => Some(err),
+            LoadErrorKind::InvalidName => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn load_directory() {
+        let dir = std::env::temp_dir().join(format!("sifis-td-server-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let lamp = json!({
+            "@context": "https://www.w3.org/2022/wot/td/v1.1",
+            "title": "Lamp",
+            "security": "nosec",
+            "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+            "sho:hazards": [{
+                "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+                "sho:conditions": [[]],
+            }],
+        });
+        fs::write(dir.join("lamp.json"), lamp.to_string()).unwrap();
+        fs::write(dir.join("notes.txt"), "not a Thing").unwrap();
+
+        let home = load(&dir).unwrap();
+        assert_eq!(home.len(), 1);
+        assert_eq!(home.get("lamp").unwrap().max_level(), Some(7));
+
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        let err = load(&dir).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Json(_)));
+        assert!(err.to_string().starts_with("invalid "));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+//! Explanation of the evaluation of the hazards against a state.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+use sifis_td::Sifis;
+
+/// Describes, for each hazard, which conditions are satisfied by the state.
+///
+/// Each condition is marked with `[x]` when satisfied, and it is followed by the value found in
+/// the state.
+pub(crate) fn explain(sifis: &Sifis, state: &Value) -> String {
+    let mut out = String::new();
+
+    if sifis.hazards.is_empty() {
+        out.push_str("no hazards\n");
+    }
+
+    for (index, hazard) in sifis.hazards.iter().enumerate() {
+        let status = if hazard.is_triggered_by(state) {
+            "triggered"
+        } else {
+            "not triggered"
+        };
+        writeln!(
+            out,
+            "#{index} {}, level {}: {status}",
+            hazard.risk.id.detail().name,
+            hazard.risk.level,
+        )
+        .unwrap();
+
+        for (branch, conditions) in hazard.conditions.iter().enumerate() {
+            let satisfied = conditions.iter().all(|cond| cond.is_satisfied_by(state));
+            let status = if satisfied {
+                "satisfied"
+            } else {
+                "not satisfied"
+            };
+            writeln!(out, "  branch #{branch}: {status}").unwrap();
+
+            for condition in conditions {
+                let mark = if condition.is_satisfied_by(state) {
+                    'x'
+                } else {
+                    ' '
+                };
+                let found = match state.pointer(&condition.pointer) {
+                    Some(value) => format!("found {value}"),
+                    None => "not found".to_owned(),
+                };
+                writeln!(out, "    [{mark}] {condition} ({found})").unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn explanation() {
+        let sifis = Sifis::builder()
+            .fire_hazard(7, |cond| {
+                cond.when("/properties/brightness")
+                    .ge(80)
+                    .and("/properties/on")
+                    .eq(true)
+                    .or(|cond| cond.when("/properties/mode").eq("boost"))
+            })
+            .build();
+
+        assert_eq!(
+            explain(
+                &sifis,
+                &json!({ "properties": { "brightness": 90, "mode": "boost" } })
+            ),
+            "#0 Fire hazard, level 7: triggered\n\
+             \x20 branch #0: not satisfied\n\
+             \x20   [x] /properties/brightness >= 80 (found 90)\n\
+             \x20   [ ] /properties/on == true (not found)\n\
+             \x20 branch #1: satisfied\n\
+             \x20   [x] /properties/mode == \"boost\" (found \"boost\")\n",
+        );
+        assert_eq!(explain(&Sifis::default(), &json!({})), "no hazards\n");
+    }
+}
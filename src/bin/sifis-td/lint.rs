@@ -0,0 +1,190 @@
+//! Consistency checks of the hazards of a Thing Description.
+
+use std::fmt::{self, Display};
+
+use sifis_td::{analysis::Domains, hazard::JsonPointer, Sifis};
+use wot_td::Thing;
+
+/// The severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a Thing Description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+}
+
+/// Checks the hazards of a Thing Description.
+///
+/// The errors are:
+///
+/// - hazards whose risk is not declared in `sho:risks`;
+/// - conditions whose JSON pointer does not refer to an affordance of the Thing.
+///
+/// The warnings are the declared risks that are not used or that differ from the catalog, and
+/// the issues found by [`Sifis::analyze_with`].
+pub(crate) fn lint(thing: &Thing<Sifis>) -> Vec<Diagnostic> {
+    let sifis = &thing.other;
+    let mut diagnostics = Vec::new();
+    let mut push = |severity, message| diagnostics.push(Diagnostic { severity, message });
+
+    for (index, detail) in sifis.risks.iter().enumerate() {
+        let name = &detail.id().detail().name;
+        if sifis.risks[..index]
+            .iter()
+            .any(|other| other.id() == detail.id())
+        {
+            push(
+                Severity::Warning,
+                format!("risk {name} is declared more than once"),
+            );
+        }
+        if detail.category() != detail.id().category() {
+            push(
+                Severity::Warning,
+                format!(
+                    "risk {name} is declared with category {}, but it is {}",
+                    detail.category(),
+                    detail.id().category(),
+                ),
+            );
+        }
+        if sifis
+            .hazards
+            .iter()
+            .all(|hazard| hazard.risk.id != detail.id())
+        {
+            push(Severity::Warning, format!("risk {name} is never used"));
+        }
+    }
+
+    for (index, hazard) in sifis.hazards.iter().enumerate() {
+        let id = hazard.risk.id;
+        if sifis.risks.iter().all(|detail| detail.id() != id) {
+            push(
+                Severity::Error,
+                format!(
+                    "hazard #{index}: risk {} is not declared in sho:risks",
+                    id.detail().name,
+                ),
+            );
+        }
+
+        for (branch, conditions) in hazard.conditions.iter().enumerate() {
+            for condition in conditions {
+                if is_affordance(thing, &condition.pointer) {
+                    continue;
+                }
+
+                push(
+                    Severity::Error,
+                    format!(
+                        "hazard #{index}, branch #{branch}: {} does not refer to an affordance \
+                         of the Thing",
+                        &*condition.pointer,
+                    ),
+                );
+            }
+        }
+    }
+
+    for issue in sifis.analyze_with(&Domains::from_thing(thing)) {
+        push(Severity::Warning, issue.to_string());
+    }
+
+    diagnostics
+}
+
+/// Checks whether the pointer refers to the data of a property, an action or an event.
+fn is_affordance(thing: &Thing<Sifis>, pointer: &JsonPointer) -> bool {
+    let mut tokens = pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"));
+
+    let (Some(kind), Some(name)) = (tokens.next(), tokens.next()) else {
+        return false;
+    };
+
+    match kind.as_str() {
+        "properties" => thing
+            .properties
+            .as_ref()
+            .is_some_and(|map| map.contains_key(&name)),
+        "actions" => thing
+            .actions
+            .as_ref()
+            .is_some_and(|map| map.contains_key(&name)),
+        "events" => thing
+            .events
+            .as_ref()
+            .is_some_and(|map| map.contains_key(&name)),
+        _ => false,
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn diagnostics() {
+        let thing: Thing<Sifis> = serde_json::from_value(json!({
+            "@context": "https://www.w3.org/2022/wot/td/v1.1",
+            "title": "Lamp",
+            "security": "nosec",
+            "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+            "properties": {
+                "a/b": { "type": "integer", "forms": [{ "href": "/a" }] },
+            },
+            "sho:risks": [{
+                "@id": "sho:TakePictures",
+                "sho:category": "sho:Safety",
+                "sho:description": "",
+                "sho:name": "",
+            }],
+            "sho:hazards": [{
+                "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+                "sho:conditions": [
+                    [{ "sho:pointer": "/properties/a~1b", "sho:condition": { "sho:value": 80, "sho:op": "ge" } }],
+                    [{ "sho:pointer": "/properties/brightness", "sho:condition": true }],
+                    [
+                        { "sho:pointer": "/properties/a~1b", "sho:condition": { "sho:value": 3, "sho:op": "lt" } },
+                        { "sho:pointer": "/properties/a~1b", "sho:condition": { "sho:value": 5, "sho:op": "gt" } },
+                    ],
+                ],
+            }],
+        }))
+        .unwrap();
+
+        let messages: Vec<String> = lint(&thing).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "warning: risk Take pictures is declared with category Safety, but it is Privacy",
+                "warning: risk Take pictures is never used",
+                "error: hazard #0: risk Fire hazard is not declared in sho:risks",
+                "error: hazard #0, branch #1: /properties/brightness does not refer to an \
+                 affordance of the Thing",
+                "warning: hazard #0, branch #2: the conditions can never be satisfied",
+            ],
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Command-line tool to lint, evaluate and render the SIFIS-Home hazards of Thing Descriptions.
+//!
+//! The Thing Descriptions are read as JSON documents containing the `sho:` extension, the states
+//! of the Things as JSON documents with the same structure of the Thing Description (i.e.:
+//! `{"properties": {"brightness": 90}}`).
+//!
+//! The exit code is 0 on success, 1 when a check fails (lint errors, or differences with
+//! `diff --exit-code`) and 2 when the input cannot be read.
+
+#![warn(clippy::pedantic)]
+
+mod explain;
+mod lint;
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    ops::Not,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::de::DeserializeOwned;
+use sifis_td::{diff, render, Sifis};
+use wot_td::Thing;
+
+use crate::lint::Severity;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Checks the consistency of the hazards of a Thing Description.
+    Lint {
+        /// The Thing Description.
+        td: PathBuf,
+
+        /// Fails on warnings too.
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+
+    /// Prints the risks triggered by a state of a Thing.
+    Eval {
+        /// The Thing Description.
+        td: PathBuf,
+
+        /// The state of the Thing.
+        state: PathBuf,
+
+        /// Prints the risks as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Shows which conditions of each hazard are satisfied by a state of a Thing.
+    Explain {
+        /// The Thing Description.
+        td: PathBuf,
+
+        /// The state of the Thing.
+        state: PathBuf,
+    },
+
+    /// Compares the hazards of two versions of a Thing Description.
+    Diff {
+        /// The old version of the Thing Description.
+        old: PathBuf,
+
+        /// The new version of the Thing Description.
+        new: PathBuf,
+
+        /// Exits with 1 if there are differences.
+        #[arg(long)]
+        exit_code: bool,
+    },
+
+    /// Renders the hazards of a Thing Description as a human-readable report.
+    Render {
+        /// The Thing Description.
+        td: PathBuf,
+
+        /// The output format.
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Markdown,
+    Html,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs a command, returning whether its checks passed.
+fn run(command: Command) -> Result<bool, InputError> {
+    match command {
+        Command::Lint { td, deny_warnings } => {
+            let thing: Thing<Sifis> = read_json(&td)?;
+            let diagnostics = lint::lint(&thing);
+            for diagnostic in &diagnostics {
+                println!("{diagnostic}");
+            }
+
+            let errors = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                .count();
+            let warnings = diagnostics.len() - errors;
+            println!("{}: {errors} error(s), {warnings} warning(s)", td.display());
+
+            Ok(errors == 0 && (warnings == 0 || deny_warnings.not()))
+        }
+        Command::Eval { td, state, json } => {
+            let thing: Thing<Sifis> = read_json(&td)?;
+            let state: serde_json::Value = read_json(&state)?;
+            let risks = thing.other.evaluate(&state);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&risks).unwrap());
+            } else if risks.is_empty() {
+                println!("no active risks");
+            } else {
+                for risk in risks {
+                    println!("{}: {}", risk.id.detail().name, risk.level);
+                }
+            }
+
+            Ok(true)
+        }
+        Command::Explain { td, state } => {
+            let thing: Thing<Sifis> = read_json(&td)?;
+            let state: serde_json::Value = read_json(&state)?;
+            print!("{}", explain::explain(&thing.other, &state));
+
+            Ok(true)
+        }
+        Command::Diff {
+            old,
+            new,
+            exit_code,
+        } => {
+            let old: Thing<Sifis> = read_json(&old)?;
+            let new: Thing<Sifis> = read_json(&new)?;
+            let report = diff(&old.other, &new.other);
+
+            if report.is_empty() {
+                println!("no differences");
+            } else {
+                print!("{report}");
+            }
+
+            Ok(report.is_empty() || exit_code.not())
+        }
+        Command::Render { td, format } => {
+            let thing: Thing<Sifis> = read_json(&td)?;
+            match format {
+                Format::Markdown => print!("{}", render::markdown(&thing)),
+                Format::Html => print!("{}", render::html(&thing)),
+            }
+
+            Ok(true)
+        }
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, InputError> {
+    let error = |kind| InputError {
+        path: path.to_owned(),
+        kind,
+    };
+
+    let data = fs::read(path).map_err(|err| error(InputErrorKind::Io(err)))?;
+    serde_json::from_slice(&data).map_err(|err| error(InputErrorKind::Json(err)))
+}
+
+/// An error reading an input file.
+#[derive(Debug)]
+struct InputError {
+    path: PathBuf,
+    kind: InputErrorKind,
+}
+
+#[derive(Debug)]
+enum InputErrorKind {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            InputErrorKind::Io(err) => write!(f, "cannot read {}: {err}", self.path.display()),
+            InputErrorKind::Json(err) => write!(f, "invalid {}: {err}", self.path.display()),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            InputErrorKind::Io(err) => Some(err),
+            InputErrorKind::Json(err) => Some(err),
+        }
+    }
+}
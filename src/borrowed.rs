@@ -0,0 +1,377 @@
+//! Zero-copy variants of the [`Sifis`] extension, borrowing from the input buffer.
+//!
+//! The owned structures always allocate the strings and the JSON pointers of the hazards while
+//! they are deserialized. The structures of this module borrow them from the input instead,
+//! copying only the strings containing escape sequences, which cannot be borrowed as they are:
+//!
+//! ```
+//! use std::borrow::Cow;
+//!
+//! use sifis_td::borrowed;
+//!
+//! let json = r#"{
+//!     "sho:hazards": [{
+//!         "sho:risk": { "@id": "sho:FireHazard", "sho:level": 3 },
+//!         "sho:conditions": [[{
+//!             "sho:pointer": "/properties/mode",
+//!             "sho:condition": "turbo"
+//!         }]]
+//!     }]
+//! }"#;
+//!
+//! let sifis: borrowed::Sifis<'_> = serde_json::from_str(json).unwrap();
+//! let condition = &sifis.hazards[0].conditions[0][0];
+//! assert!(matches!(condition.pointer, Cow::Borrowed("/properties/mode")));
+//!
+//! let owned = sifis.into_owned();
+//! assert_eq!(owned, serde_json::from_str(json).unwrap());
+//! ```
+//!
+//! The structures are serialized in the same way as their owned counterparts.
+//!
+//! [`Sifis`]: crate::Sifis
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use serde::{de::value::StrDeserializer, Deserialize, Serialize};
+
+use crate::{
+    condition::{self, Operation},
+    hazard, risk,
+};
+
+/// The Sifis extension for a Thing Description, borrowing from the input.
+///
+/// See [`crate::Sifis`] for more information.
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Sifis<'a> {
+    /// A set of possible risks used by the thing.
+    #[serde(
+        borrow,
+        skip_serializing_if = "Vec::is_empty",
+        rename = "sho:risks",
+        default
+    )]
+    pub risks: Vec<Detail<'a>>,
+
+    /// The hazards for the thing.
+    #[serde(
+        borrow,
+        skip_serializing_if = "Vec::is_empty",
+        rename = "sho:hazards",
+        default
+    )]
+    pub hazards: Vec<Hazard<'a>>,
+}
+
+impl Sifis<'_> {
+    /// Converts the extension to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> crate::Sifis {
+        crate::Sifis {
+            risks: self.risks.into_iter().map(Detail::into_owned).collect(),
+            hazards: self.hazards.into_iter().map(Hazard::into_owned).collect(),
+        }
+    }
+}
+
+/// The details of a risk, borrowing from the input.
+///
+/// See [`risk::Detail`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Detail<'a> {
+    #[serde(rename = "@id")]
+    id: hazard::Id,
+
+    #[serde(rename = "sho:category")]
+    category: hazard::Category,
+
+    /// The description of the hazard, in human-readable form.
+    #[serde(borrow, rename = "sho:description")]
+    pub description: Cow<'a, str>,
+
+    /// The name of the hazard, in human-readable form.
+    #[serde(borrow, rename = "sho:name")]
+    pub name: Cow<'a, str>,
+}
+
+impl Detail<'_> {
+    /// The ID of the hazard.
+    #[must_use]
+    pub fn id(&self) -> hazard::Id {
+        self.id
+    }
+
+    /// The category of the hazard.
+    #[must_use]
+    pub fn category(&self) -> hazard::Category {
+        self.category
+    }
+
+    /// Converts the details to their owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> risk::Detail {
+        risk::Detail::from_parts(
+            self.id,
+            self.category,
+            Cow::Owned(self.description.into_owned()),
+            Cow::Owned(self.name.into_owned()),
+        )
+    }
+}
+
+/// An hazard element, borrowing from the input.
+///
+/// See [`hazard::Hazard`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Hazard<'a> {
+    /// The associated risk.
+    #[serde(rename = "sho:risk")]
+    pub risk: hazard::Risk,
+
+    /// The set of conditions that are required in order to trigger the hazard.
+    ///
+    /// The inner vectors express a logic `AND`, the outer one a logic `OR`.
+    #[serde(
+        borrow,
+        rename = "sho:conditions",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub conditions: Vec<Vec<Condition<'a>>>,
+}
+
+impl Hazard<'_> {
+    /// Converts the hazard to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> hazard::Hazard {
+        hazard::Hazard {
+            risk: self.risk,
+            conditions: self
+                .conditions
+                .into_iter()
+                .map(|conditions| conditions.into_iter().map(Condition::into_owned).collect())
+                .collect(),
+        }
+    }
+}
+
+/// A condition on the data of a Thing, borrowing from the input.
+///
+/// See [`hazard::Condition`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Condition<'a> {
+    /// The JSON pointer for the expression to be evaluated.
+    ///
+    /// The pointer is not validated until it is converted to a [`hazard::JsonPointer`].
+    #[serde(borrow, rename = "sho:pointer")]
+    pub pointer: Cow<'a, str>,
+
+    /// The condition that needs to be satisfied.
+    #[serde(borrow, rename = "sho:condition")]
+    pub condition: ConditionKind<'a>,
+}
+
+impl Condition<'_> {
+    /// Converts the condition to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> hazard::Condition {
+        hazard::Condition {
+            pointer: hazard::JsonPointer(owned_pointer(&self.pointer)),
+            condition: self.condition.into_owned(),
+        }
+    }
+}
+
+/// Converts a JSON pointer to its owned representation.
+///
+/// Going through the deserializer of the pointer keeps the same behavior of the owned structures
+/// for malformed pointers, which are stored instead of being rejected.
+fn owned_pointer(pointer: &str) -> jsonptr::Pointer {
+    jsonptr::Pointer::deserialize(StrDeserializer::<serde::de::value::Error>::new(pointer))
+        .expect("JSON pointers are always deserialized from strings")
+}
+
+/// A condition, as expression or value, borrowing from the input.
+///
+/// See [`condition::Condition`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConditionKind<'a> {
+    /// A simple way to express value equality.
+    Value(#[serde(borrow)] Value<'a>),
+
+    /// A custom conditional expression.
+    Expr(#[serde(borrow)] Expr<'a>),
+}
+
+impl ConditionKind<'_> {
+    /// Converts the condition to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> condition::Condition {
+        match self {
+            Self::Value(value) => condition::Condition::Value(value.into_owned()),
+            Self::Expr(expr) => condition::Condition::Expr(expr.into_owned()),
+        }
+    }
+}
+
+/// A value to be used for conditions, borrowing from the input.
+///
+/// See [`condition::Value`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value<'a> {
+    /// A boolean value.
+    Bool(bool),
+
+    /// A numeric value.
+    Number(serde_json::Number),
+
+    /// A string value.
+    String(#[serde(borrow)] Cow<'a, str>),
+}
+
+impl Value<'_> {
+    /// Converts the value to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> condition::Value {
+        match self {
+            Self::Bool(value) => condition::Value::Bool(value),
+            Self::Number(value) => condition::Value::Number(value),
+            Self::String(value) => condition::Value::String(Cow::Owned(value.into_owned())),
+        }
+    }
+}
+
+/// A conditional expression, borrowing from the input.
+///
+/// See [`condition::Expr`] for more information.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Expr<'a> {
+    /// The value to be compared.
+    #[serde(borrow, rename = "sho:value")]
+    pub value: Value<'a>,
+
+    /// The operation to be used in order to evaluate the [`value`].
+    ///
+    /// [`value`]: Expr::value
+    #[serde(rename = "sho:op")]
+    pub op: Operation,
+}
+
+impl Expr<'_> {
+    /// Converts the expression to its owned counterpart.
+    #[must_use]
+    pub fn into_owned(self) -> condition::Expr {
+        condition::Expr {
+            value: self.value.into_owned(),
+            op: self.op,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn json() -> serde_json::Value {
+        json!({
+            "sho:risks": [{
+                "@id": "sho:FireHazard",
+                "sho:category": "sho:Safety",
+                "sho:description": "The execution may cause fire",
+                "sho:name": "Fire \"hazard\"",
+            }],
+            "sho:hazards": [
+                {
+                    "sho:risk": { "@id": "sho:FireHazard", "sho:level": 3 },
+                    "sho:conditions": [
+                        [
+                            {
+                                "sho:pointer": "/properties/level",
+                                "sho:condition": { "sho:value": 4.5, "sho:op": "ge" },
+                            },
+                            {
+                                "sho:pointer": "/properties/mode",
+                                "sho:condition": { "sho:value": "eco", "sho:op": "ne" },
+                            },
+                        ],
+                        [{
+                            "sho:pointer": "/properties/a~1b",
+                            "sho:condition": true,
+                        }],
+                    ],
+                },
+                {
+                    "sho:risk": { "@id": "sho:Explosion", "sho:level": 1 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "properties/invalid",
+                        "sho:condition": "turbo",
+                    }]],
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn borrows_from_input() {
+        let input = json().to_string();
+        let sifis: Sifis<'_> = serde_json::from_str(&input).unwrap();
+
+        let detail = &sifis.risks[0];
+        assert_eq!(detail.id(), hazard::Id::FireHazard);
+        assert_eq!(detail.category(), hazard::Category::Safety);
+        assert!(matches!(
+            detail.description,
+            Cow::Borrowed("The execution may cause fire")
+        ));
+        // Escaped strings cannot be borrowed.
+        assert!(matches!(&detail.name, Cow::Owned(name) if name == "Fire \"hazard\""));
+
+        let conditions = &sifis.hazards[0].conditions;
+        assert!(matches!(
+            conditions[0][0].pointer,
+            Cow::Borrowed("/properties/level")
+        ));
+        assert!(matches!(
+            conditions[0][1].condition,
+            ConditionKind::Expr(Expr {
+                value: Value::String(Cow::Borrowed("eco")),
+                op: Operation::Ne,
+            })
+        ));
+        assert!(matches!(
+            sifis.hazards[1].conditions[0][0].condition,
+            ConditionKind::Value(Value::String(Cow::Borrowed("turbo")))
+        ));
+    }
+
+    #[test]
+    fn into_owned() {
+        let input = json().to_string();
+        let borrowed: Sifis<'_> = serde_json::from_str(&input).unwrap();
+        let owned: crate::Sifis = serde_json::from_str(&input).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&borrowed).unwrap(),
+            serde_json::to_value(&owned).unwrap(),
+        );
+
+        let converted = borrowed.into_owned();
+        assert_eq!(converted, owned);
+
+        // Malformed pointers are kept as they are by the owned structures too.
+        let pointer = &converted.hazards[1].conditions[0][0].pointer;
+        assert_eq!(&**pointer, "properties/invalid");
+        assert!(converted.hazards[1]
+            .conditions
+            .iter()
+            .flatten()
+            .all(|condition| !condition.is_satisfied_by(&json!({ "properties": {} }))));
+    }
+}
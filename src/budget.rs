@@ -0,0 +1,351 @@
+//! Tracking of the cumulative exposure to financial risks.
+//!
+//! The impact of the [`Financial`] risks, like the consumption of energy or the payment of a
+//! subscription, accumulates over time. A [`BudgetTracker`] integrates the level of each active
+//! financial risk over time, per Thing and per [`hazard::Id`], obtaining an _exposure_: a risk
+//! with level 3 active for 2 hours has an exposure of 6 hours.
+//!
+//! The tracker is fed with the [`RiskChanged`] transitions produced by an
+//! [`IncrementalEvaluator`], and it emits a [`BudgetAlert`] when the exposure exceeds one of the
+//! configured [`Budget`]s.
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//!
+//! use serde_json::json;
+//! use sifis_td::{
+//!     budget::{Budget, BudgetTracker},
+//!     hazard,
+//!     incremental::IncrementalEvaluator,
+//!     Sifis,
+//! };
+//!
+//! const HOUR: Duration = Duration::from_secs(3600);
+//!
+//! let heater = Sifis::builder()
+//!     .electric_energy_consumption(2, |cond| cond.when("/properties/on").eq(true))
+//!     .build();
+//! let mut evaluator = IncrementalEvaluator::new(&heater, json!({}));
+//!
+//! let mut tracker = BudgetTracker::new();
+//! tracker.add_budget(Budget {
+//!     id: hazard::Id::ElectricEnergyConsumption,
+//!     thing: None,
+//!     limit: 3 * HOUR,
+//! });
+//!
+//! let start = Instant::now();
+//! let on = "/properties/on".try_into().unwrap();
+//! for change in evaluator.apply_change(&on, json!(true)).unwrap() {
+//!     tracker.record("heater", change, start);
+//! }
+//!
+//! assert!(tracker.advance(start + HOUR).is_empty());
+//! let alerts = tracker.advance(start + 2 * HOUR);
+//! assert_eq!(alerts[0].exposure, 4 * HOUR);
+//! ```
+//!
+//! [`Financial`]: hazard::Category::Financial
+//! [`IncrementalEvaluator`]: crate::incremental::IncrementalEvaluator
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::Not,
+    time::{Duration, Instant},
+};
+
+use crate::{hazard, incremental::RiskChanged};
+
+/// A limit to the exposure to a financial risk.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Budget<K> {
+    /// The id of the risk.
+    pub id: hazard::Id,
+
+    /// The Thing the budget applies to, or `None` for the sum of all the Things.
+    pub thing: Option<K>,
+
+    /// The maximum exposure, as the integral of the level of the risk over time.
+    pub limit: Duration,
+}
+
+/// The notification that the exposure exceeded a [`Budget`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BudgetAlert<K> {
+    /// The exceeded budget.
+    pub budget: Budget<K>,
+
+    /// The exposure when the alert was emitted.
+    pub exposure: Duration,
+}
+
+/// An accumulator of the exposure to financial risks of multiple Things.
+///
+/// Time is provided by the caller, in order to be independent from any clock or runtime. Each
+/// budget produces at most one alert, until [`reset`] is called.
+///
+/// [`reset`]: BudgetTracker::reset
+#[derive(Debug, Clone)]
+pub struct BudgetTracker<K> {
+    active: HashMap<(K, hazard::Id), Active>,
+    exposures: HashMap<(K, hazard::Id), Duration>,
+    budgets: Vec<(Budget<K>, bool)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Active {
+    level: u8,
+    since: Instant,
+}
+
+impl<K> Default for BudgetTracker<K> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            active: HashMap::new(),
+            exposures: HashMap::new(),
+            budgets: Vec::new(),
+        }
+    }
+}
+
+impl<K> BudgetTracker<K>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Creates a tracker without budgets.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a budget.
+    pub fn add_budget(&mut self, budget: Budget<K>) {
+        self.budgets.push((budget, false));
+    }
+
+    /// Returns the budgets.
+    pub fn budgets(&self) -> impl Iterator<Item = &Budget<K>> {
+        self.budgets.iter().map(|(budget, _)| budget)
+    }
+
+    /// Records the change of level of a risk of a Thing at the given time.
+    ///
+    /// The exposure of the risk is updated up to `at`, then the new level is used. Changes of
+    /// non-financial risks are ignored.
+    pub fn record(&mut self, thing: K, change: RiskChanged, at: Instant) -> Vec<BudgetAlert<K>> {
+        if change.id.category() != hazard::Category::Financial {
+            return Vec::new();
+        }
+
+        let key = (thing, change.id);
+        if let Some(active) = self.active.remove(&key) {
+            *self.exposures.entry(key.clone()).or_default() += active.exposure(at);
+        }
+        if let Some(level) = change.new_level.filter(|&level| level > 0) {
+            self.active.insert(key, Active { level, since: at });
+        }
+
+        self.alerts(at)
+    }
+
+    /// Updates the exposures of the active risks up to the given time.
+    pub fn advance(&mut self, at: Instant) -> Vec<BudgetAlert<K>> {
+        for (key, active) in &mut self.active {
+            *self.exposures.entry(key.clone()).or_default() += active.exposure(at);
+            active.since = active.since.max(at);
+        }
+
+        self.alerts(at)
+    }
+
+    /// Returns the exposure of a Thing to a risk, up to the given time.
+    #[must_use]
+    pub fn exposure(&self, thing: &K, id: hazard::Id, at: Instant) -> Duration {
+        self.exposures_until(at)
+            .filter(|(key, _)| &key.0 == thing && key.1 == id)
+            .map(|(_, exposure)| exposure)
+            .sum()
+    }
+
+    /// Returns the exposure of all the Things to a risk, up to the given time.
+    #[must_use]
+    pub fn total_exposure(&self, id: hazard::Id, at: Instant) -> Duration {
+        self.exposures_until(at)
+            .filter(|(key, _)| key.1 == id)
+            .map(|(_, exposure)| exposure)
+            .sum()
+    }
+
+    /// Clears the exposures and re-enables the alerts, i.e. for a new billing period.
+    ///
+    /// The active risks keep accumulating exposure from `at`.
+    pub fn reset(&mut self, at: Instant) {
+        self.exposures.clear();
+        for active in self.active.values_mut() {
+            active.since = at;
+        }
+        for (_, exceeded) in &mut self.budgets {
+            *exceeded = false;
+        }
+    }
+
+    /// The exposures up to the given time, with an entry for each stored exposure and each
+    /// active risk.
+    fn exposures_until(&self, at: Instant) -> impl Iterator<Item = (&(K, hazard::Id), Duration)> {
+        self.exposures
+            .iter()
+            .map(|(key, &exposure)| (key, exposure))
+            .chain(
+                self.active
+                    .iter()
+                    .map(move |(key, active)| (key, active.exposure(at))),
+            )
+    }
+
+    fn alerts(&mut self, at: Instant) -> Vec<BudgetAlert<K>> {
+        let mut alerts = Vec::new();
+        for index in 0..self.budgets.len() {
+            let (budget, exceeded) = &self.budgets[index];
+            if *exceeded {
+                continue;
+            }
+
+            let exposure = match &budget.thing {
+                Some(thing) => self.exposure(thing, budget.id, at),
+                None => self.total_exposure(budget.id, at),
+            };
+            if exposure > budget.limit {
+                alerts.push(BudgetAlert {
+                    budget: budget.clone(),
+                    exposure,
+                });
+                self.budgets[index].1 = true;
+            }
+        }
+
+        alerts
+    }
+}
+
+impl Active {
+    #[inline]
+    fn exposure(&self, at: Instant) -> Duration {
+        at.saturating_duration_since(self.since)
+            .saturating_mul(u32::from(self.level))
+    }
+}
+
+impl<K> BudgetAlert<K> {
+    /// Returns how much the exposure exceeds the limit of the budget.
+    #[inline]
+    #[must_use]
+    pub fn excess(&self) -> Duration {
+        self.exposure.saturating_sub(self.budget.limit)
+    }
+}
+
+impl<K> Budget<K> {
+    /// Checks whether the budget applies to the given Thing and risk.
+    #[inline]
+    #[must_use]
+    pub fn applies_to(&self, thing: &K, id: hazard::Id) -> bool
+    where
+        K: PartialEq,
+    {
+        self.id == id && self.thing.as_ref().is_some_and(|cur| cur != thing).not()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `Duration::from_hours` requires a much newer toolchain than the rest of the crate.
+    #[allow(clippy::duration_suboptimal_units)]
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn change(id: hazard::Id, old_level: Option<u8>, new_level: Option<u8>) -> RiskChanged {
+        RiskChanged {
+            id,
+            old_level,
+            new_level,
+        }
+    }
+
+    #[test]
+    fn exposure() {
+        let start = Instant::now();
+        let mut tracker = BudgetTracker::new();
+        let water = hazard::Id::WaterConsumption;
+        let gas = hazard::Id::GasConsumption;
+
+        tracker.record("tap", change(water, None, Some(2)), start);
+        tracker.record("shower", change(water, None, Some(3)), start + HOUR);
+        tracker.record("tap", change(water, Some(2), Some(1)), start + 2 * HOUR);
+        tracker.record("shower", change(water, Some(3), None), start + 3 * HOUR);
+        tracker.record("tap", change(hazard::Id::Scald, None, Some(9)), start);
+        tracker.record("hob", change(gas, None, Some(4)), start);
+
+        let at = start + 4 * HOUR;
+        assert_eq!(tracker.exposure(&"tap", water, at), 6 * HOUR);
+        assert_eq!(tracker.exposure(&"shower", water, at), 6 * HOUR);
+        assert_eq!(tracker.total_exposure(water, at), 12 * HOUR);
+        assert_eq!(tracker.exposure(&"hob", gas, at), 16 * HOUR);
+        assert_eq!(
+            tracker.exposure(&"tap", hazard::Id::Scald, at),
+            Duration::ZERO
+        );
+
+        tracker.advance(at);
+        assert_eq!(tracker.total_exposure(water, at), 12 * HOUR);
+        tracker.reset(at);
+        assert_eq!(tracker.total_exposure(water, at + HOUR), HOUR);
+    }
+
+    #[test]
+    fn alerts() {
+        let start = Instant::now();
+        let mut tracker = BudgetTracker::new();
+        let money = hazard::Id::SpendMoney;
+        tracker.add_budget(Budget {
+            id: money,
+            thing: Some("shop"),
+            limit: 2 * HOUR,
+        });
+        tracker.add_budget(Budget {
+            id: money,
+            thing: None,
+            limit: 5 * HOUR,
+        });
+
+        assert!(tracker
+            .record("shop", change(money, None, Some(1)), start)
+            .is_empty());
+        assert!(tracker
+            .record("tv", change(money, None, Some(2)), start)
+            .is_empty());
+
+        assert!(tracker.advance(start + HOUR + HOUR / 2).is_empty());
+
+        let alerts = tracker.advance(start + 2 * HOUR);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].budget.thing, None);
+        assert_eq!(alerts[0].excess(), HOUR);
+
+        let alerts = tracker.record("shop", change(money, Some(1), None), start + 3 * HOUR);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].budget.thing, Some("shop"));
+        assert_eq!(alerts[0].exposure, 3 * HOUR);
+
+        assert!(tracker.advance(start + 10 * HOUR).is_empty());
+        assert!(tracker
+            .budgets()
+            .all(|budget| budget.applies_to(&"shop", money)));
+        assert!(tracker
+            .budgets()
+            .any(|budget| budget.applies_to(&"tv", money).not()));
+    }
+}
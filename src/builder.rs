@@ -44,7 +44,7 @@ use std::{fmt::Display, ops::Not};
 use crate::{
     condition,
     hazard::{self, Hazard, JsonPointer},
-    risk, Sifis,
+    merge, risk, Sifis,
 };
 
 /// A _builder_ for [`Sifis`].
@@ -150,11 +150,36 @@ impl Builder {
         };
         condition(Condition(&mut hazard.conditions));
         self.hazards.push(hazard);
+        merge::insert_risk(&mut self.risks, id.risk());
 
-        let risk = self.risks.iter().find(|risk| risk.id() == id);
-        if risk.is_none() {
-            self.risks.push(id.risk());
+        self
+    }
+
+    /// Extends the builder with the risks and the hazards of an already built [`Sifis`].
+    ///
+    /// This is useful to compose the extension of a _Thing_ from multiple modules. The risks are
+    /// deduplicated by their [`Id`], while the hazards are appended to the current ones. See
+    /// [`Sifis::merge`] for more information.
+    ///
+    /// # Panics
+    ///
+    /// The function panics if a risk of `sifis` has the same [`Id`] of a risk already in the
+    /// builder but with different details. Use [`Sifis::merge`] in order to handle the conflicts.
+    ///
+    /// [`Id`]: hazard::Id
+    #[must_use]
+    pub fn extend(mut self, sifis: Sifis) -> Self {
+        let Sifis { risks, hazards } = sifis;
+        for risk in risks {
+            if let Some(cur) = merge::insert_risk(&mut self.risks, risk.clone()) {
+                assert!(
+                    *cur == risk,
+                    "conflicting details for risk with id {:?}",
+                    risk.id(),
+                );
+            }
         }
+        self.hazards.extend(hazards);
 
         self
     }
@@ -674,6 +699,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extend() {
+        let sifis = Sifis::builder()
+            .fire_hazard(1, |cond| cond)
+            .extend(
+                Sifis::builder()
+                    .explosion(2, |cond| cond)
+                    .fire_hazard(3, |cond| cond)
+                    .build(),
+            )
+            .explosion(4, |cond| cond)
+            .build();
+
+        assert_eq!(sifis.risks, [FIRE_HAZARD, EXPLOSION]);
+        assert_eq!(
+            sifis
+                .hazards
+                .iter()
+                .map(|hazard| hazard.risk.level)
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4],
+        );
+    }
+
+    #[test]
+    #[should_panic = "conflicting details for risk with id FireHazard"]
+    fn extend_conflicting() {
+        let mut custom_fire = FIRE_HAZARD;
+        custom_fire.name = "Custom fire".into();
+
+        let _ = Sifis::builder().fire_hazard(1, |cond| cond).extend(Sifis {
+            risks: vec![custom_fire],
+            hazards: Vec::new(),
+        });
+    }
+
     #[test]
     fn short_form() {
         let sifis = Sifis::builder()
@@ -0,0 +1,371 @@
+//! Fast evaluation of hazards for frequent state updates.
+//!
+//! [`Sifis::evaluate`] walks the JSON pointers and matches the conditions each time it is
+//! called, which is wasteful when the same hazards are evaluated for every change of the state
+//! of a Thing. A [`CompiledSifis`] does this work once:
+//!
+//! - the JSON pointers are parsed into paths of tokens, and the same pointer is resolved only
+//!   once per evaluation;
+//! - the values of the conditions are converted to a representation that can be compared
+//!   cheaply;
+//! - the hazards are indexed by the JSON pointers they depend on, see
+//!   [`CompiledSifis::affected_hazards`].
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{hazard, Sifis};
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .take_pictures(5, |cond| cond.when("/properties/camera/on").eq(true))
+//!     .build();
+//! let compiled = sifis.compile();
+//!
+//! let state = json!({ "properties": { "brightness": 50, "camera": { "on": false } } });
+//! assert_eq!(compiled.evaluate(&state), sifis.evaluate(&state));
+//! assert_eq!(compiled.affected_hazards("/properties/camera"), [1]);
+//! ```
+
+use std::{cmp::Ordering, collections::BTreeMap, ops::Bound};
+
+use serde_json::Value;
+
+use crate::{
+    condition::{self, ComparableNumber, Operation},
+    hazard, insert_max_risk, Sifis,
+};
+
+impl Sifis {
+    /// Compiles the hazards for a faster evaluation.
+    ///
+    /// See [`CompiledSifis`] for more information.
+    #[inline]
+    #[must_use]
+    pub fn compile(&self) -> CompiledSifis {
+        CompiledSifis::from(self)
+    }
+}
+
+/// A representation of the hazards of a [`Sifis`] extension optimized for the evaluation.
+///
+/// The evaluation gives the same results of [`Sifis::evaluate`], but it is faster. The hazards
+/// are identified by their index in [`Sifis::hazards`].
+#[derive(Debug, Clone)]
+pub struct CompiledSifis {
+    paths: Vec<Path>,
+    hazards: Vec<CompiledHazard>,
+    dependents: BTreeMap<Box<str>, Vec<usize>>,
+}
+
+#[derive(Debug, Clone)]
+struct Path {
+    /// The tokens of the pointer, or `None` if the pointer is malformed.
+    tokens: Option<Box<[Token]>>,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    key: Box<str>,
+    index: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledHazard {
+    risk: hazard::Risk,
+    branches: Box<[Box<[CompiledCondition]>]>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledCondition {
+    path: usize,
+    /// The operation of the condition, `None` for equality.
+    op: Option<Operation>,
+    value: Scalar,
+}
+
+#[derive(Debug, Clone)]
+enum Scalar {
+    Bool(bool),
+    Number(ComparableNumber),
+    String(Box<str>),
+}
+
+impl From<&Sifis> for CompiledSifis {
+    fn from(sifis: &Sifis) -> Self {
+        let mut path_indices = BTreeMap::<&str, usize>::new();
+        let mut paths = Vec::new();
+        let mut dependents = BTreeMap::<Box<str>, Vec<usize>>::new();
+
+        let hazards = sifis
+            .hazards
+            .iter()
+            .enumerate()
+            .map(|(hazard_index, hazard)| {
+                let branches = hazard
+                    .conditions
+                    .iter()
+                    .map(|conditions| {
+                        conditions
+                            .iter()
+                            .map(|condition| {
+                                let pointer = &condition.pointer;
+                                let path =
+                                    *path_indices.entry(pointer.as_ref()).or_insert_with(|| {
+                                        paths.push(Path::new(&pointer.0));
+                                        paths.len() - 1
+                                    });
+
+                                let hazards =
+                                    dependents.entry(pointer.as_ref().into()).or_default();
+                                if hazards.last() != Some(&hazard_index) {
+                                    hazards.push(hazard_index);
+                                }
+
+                                CompiledCondition::new(path, &condition.condition)
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                CompiledHazard {
+                    risk: hazard.risk,
+                    branches,
+                }
+            })
+            .collect();
+
+        Self {
+            paths,
+            hazards,
+            dependents,
+        }
+    }
+}
+
+impl CompiledSifis {
+    /// Evaluates the risks triggered by the state of a Thing.
+    ///
+    /// See [`Sifis::evaluate`] for more information.
+    #[must_use]
+    pub fn evaluate(&self, state: &Value) -> Vec<hazard::Risk> {
+        let values: Vec<_> = self.paths.iter().map(|path| path.resolve(state)).collect();
+
+        self.hazards
+            .iter()
+            .filter(|hazard| hazard.is_triggered(&values))
+            .fold(Vec::new(), |mut risks, hazard| {
+                insert_max_risk(&mut risks, hazard.risk);
+                risks
+            })
+    }
+
+    /// Checks whether the hazard with the given index is triggered by the state of a Thing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[must_use]
+    pub fn is_triggered(&self, hazard: usize, state: &Value) -> bool {
+        self.hazards[hazard].branches.iter().any(|conditions| {
+            conditions.iter().all(|condition| {
+                self.paths[condition.path]
+                    .resolve(state)
+                    .is_some_and(|value| condition.is_satisfied_by(value))
+            })
+        })
+    }
+
+    /// Returns the risk of the hazard with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn risk(&self, hazard: usize) -> hazard::Risk {
+        self.hazards[hazard].risk
+    }
+
+    /// Returns the number of hazards.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hazards.len()
+    }
+
+    /// Returns `true` if there are no hazards.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hazards.is_empty()
+    }
+
+    /// Returns the sorted indices of the hazards that can change when the data behind the JSON
+    /// pointer changes.
+    ///
+    /// These are the hazards with conditions on the pointer itself, on one of its ancestors or on
+    /// one of its descendants.
+    #[must_use]
+    pub fn affected_hazards(&self, pointer: &str) -> Vec<usize> {
+        let ancestors = pointer
+            .match_indices('/')
+            .map(|(index, _)| &pointer[..index])
+            .chain([pointer])
+            .filter_map(|ancestor| self.dependents.get(ancestor));
+
+        // The descendants of `/a` are sorted between `/a/` and `/a0`, because '0' follows '/'.
+        let descendants = self
+            .dependents
+            .range::<str, _>((
+                Bound::Excluded(&*format!("{pointer}/")),
+                Bound::Excluded(&*format!("{pointer}0")),
+            ))
+            .map(|(_, hazards)| hazards)
+            .chain(self.dependents.get(&*format!("{pointer}/")));
+
+        let mut hazards: Vec<usize> = ancestors.chain(descendants).flatten().copied().collect();
+        hazards.sort_unstable();
+        hazards.dedup();
+        hazards
+    }
+}
+
+impl Path {
+    fn new(pointer: &jsonptr::Pointer) -> Self {
+        let tokens = pointer.is_valid().then(|| {
+            pointer
+                .tokens()
+                .map(|token| Token {
+                    index: token.decoded().parse().ok(),
+                    key: token.decoded().into(),
+                })
+                .collect()
+        });
+
+        Self { tokens }
+    }
+
+    fn resolve<'a>(&self, mut value: &'a Value) -> Option<&'a Value> {
+        for token in self.tokens.as_deref()? {
+            value = match value {
+                Value::Object(map) => map.get(&*token.key)?,
+                Value::Array(array) => array.get(token.index?)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+}
+
+impl CompiledHazard {
+    fn is_triggered(&self, values: &[Option<&Value>]) -> bool {
+        self.branches.iter().any(|conditions| {
+            conditions.iter().all(|condition| {
+                values[condition.path].is_some_and(|value| condition.is_satisfied_by(value))
+            })
+        })
+    }
+}
+
+impl CompiledCondition {
+    fn new(path: usize, condition: &condition::Condition) -> Self {
+        let (value, op) = match condition {
+            condition::Condition::Value(value) => (value, None),
+            condition::Condition::Expr(condition::Expr { value, op }) => (value, Some(*op)),
+        };
+
+        let value = match value {
+            condition::Value::Bool(value) => Scalar::Bool(*value),
+            condition::Value::Number(value) => Scalar::Number(value.into()),
+            condition::Value::String(value) => Scalar::String((**value).into()),
+        };
+
+        Self { path, op, value }
+    }
+
+    #[inline]
+    fn is_satisfied_by(&self, value: &Value) -> bool {
+        let ordering = match (value, &self.value) {
+            (Value::Bool(a), Scalar::Bool(b)) => Some(a.cmp(b)),
+            (Value::Number(a), Scalar::Number(b)) => ComparableNumber::from(a).partial_cmp(b),
+            (Value::String(a), Scalar::String(b)) => Some(a.as_str().cmp(b)),
+            _ => None,
+        };
+
+        ordering.is_some_and(|ordering| match self.op {
+            None => ordering == Ordering::Equal,
+            Some(Operation::Lt) => ordering.is_lt(),
+            Some(Operation::Le) => ordering.is_le(),
+            Some(Operation::Ne) => ordering.is_ne(),
+            Some(Operation::Gt) => ordering.is_gt(),
+            Some(Operation::Ge) => ordering.is_ge(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sifis() -> Sifis {
+        Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(3)
+                    .and("/properties/on")
+                    .eq(true)
+            })
+            .explosion(1, |cond| {
+                cond.when("/properties/pressure/0")
+                    .gt(-1.5)
+                    .or(|cond| cond.when("/properties/mode").ne("safe"))
+            })
+            .fire_hazard(7, |cond| cond.when("/properties/level").gt(u64::MAX - 1))
+            .take_pictures(2, |cond| cond.when("/properties/a~1b").eq(true))
+            .build()
+    }
+
+    #[test]
+    fn same_results() {
+        let sifis = sifis();
+        let compiled = sifis.compile();
+        assert_eq!(compiled.len(), 4);
+
+        let states = [
+            json!({}),
+            json!({ "properties": { "level": 3, "on": true } }),
+            json!({ "properties": { "level": 2.9, "on": true, "mode": "safe" } }),
+            json!({ "properties": { "level": u64::MAX, "on": "true", "mode": "fast" } }),
+            json!({ "properties": { "level": -3, "pressure": [-1.4], "a/b": true } }),
+            json!({ "properties": { "pressure": { "0": 0 }, "a~1b": true } }),
+            json!({ "properties": { "pressure": [-2], "mode": 1 } }),
+        ];
+
+        for state in &states {
+            assert_eq!(compiled.evaluate(state), sifis.evaluate(state), "{state}");
+            for (index, hazard) in sifis.hazards.iter().enumerate() {
+                assert_eq!(
+                    compiled.is_triggered(index, state),
+                    hazard.is_triggered_by(state),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn affected_hazards() {
+        let compiled = sifis().compile();
+
+        assert_eq!(compiled.affected_hazards("/properties/level"), [0, 2]);
+        assert_eq!(compiled.affected_hazards("/properties/pressure/0"), [1]);
+        assert_eq!(compiled.affected_hazards("/properties/pressure"), [1]);
+        assert_eq!(compiled.affected_hazards("/properties"), [0, 1, 2, 3]);
+        assert_eq!(compiled.affected_hazards(""), [0, 1, 2, 3]);
+        assert_eq!(compiled.affected_hazards("/properties/lev"), [0; 0]);
+        assert_eq!(compiled.affected_hazards("/properties/level/inner"), [0, 2]);
+        assert_eq!(compiled.affected_hazards("/actions"), [0; 0]);
+    }
+}
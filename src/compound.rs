@@ -0,0 +1,272 @@
+//! Hazards arising from the combined states of multiple Things.
+//!
+//! The conditions of a [`Hazard`] refer to the data of a single Thing Description. Some dangers
+//! only arise from combinations, like a gas hob turned on while the window is closed and the
+//! extractor is off. The conditions of a [`CompoundHazard`] refer to a Thing by its id, and they
+//! are evaluated against the states of the Things of a [`Home`].
+//!
+//! The compound hazards are described by a standalone [`CompoundRules`] document:
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{compound::CompoundRules, hazard, home::Home, Sifis};
+//!
+//! let rules: CompoundRules = serde_json::from_value(json!({
+//!     "sho:hazards": [{
+//!         "sho:risk": { "@id": "sho:Asphyxia", "sho:level": 8 },
+//!         "sho:conditions": [[
+//!             {
+//!                 "sho:thing": "hob",
+//!                 "sho:pointer": "/properties/on",
+//!                 "sho:condition": true,
+//!             },
+//!             {
+//!                 "sho:thing": "window",
+//!                 "sho:pointer": "/properties/open",
+//!                 "sho:condition": false,
+//!             },
+//!         ]],
+//!     }],
+//! }))
+//! .unwrap();
+//!
+//! let mut home = Home::new();
+//! home.insert("hob", Sifis::default(), json!({ "properties": { "on": true } }));
+//! home.insert("window", Sifis::default(), json!({ "properties": { "open": true } }));
+//! assert_eq!(rules.evaluate(&home), []);
+//!
+//! home.update_state("window", json!({ "properties": { "open": false } }));
+//! assert_eq!(
+//!     rules.evaluate(&home),
+//!     [hazard::Risk {
+//!         id: hazard::Id::Asphyxia,
+//!         level: 8,
+//!     }],
+//! );
+//! ```
+//!
+//! [`Hazard`]: hazard::Hazard
+
+use std::borrow::Borrow;
+
+use jsonptr::Resolve;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    condition,
+    hazard::{self, JsonPointer},
+    home::Home,
+    insert_max_risk,
+};
+
+/// A set of hazards spanning multiple Things.
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct CompoundRules {
+    /// The compound hazards.
+    #[serde(skip_serializing_if = "Vec::is_empty", rename = "sho:hazards", default)]
+    pub hazards: Vec<CompoundHazard>,
+}
+
+impl CompoundRules {
+    /// Evaluates the risks triggered by the states of the Things of a home.
+    ///
+    /// The risks are aggregated like in [`Sifis::evaluate`].
+    ///
+    /// [`Sifis::evaluate`]: crate::Sifis::evaluate
+    #[must_use]
+    pub fn evaluate<K>(&self, home: &Home<K>) -> Vec<hazard::Risk>
+    where
+        K: Ord + Borrow<str>,
+    {
+        self.hazards
+            .iter()
+            .filter(|hazard| hazard.is_triggered_by(home))
+            .fold(Vec::new(), |mut risks, hazard| {
+                insert_max_risk(&mut risks, hazard.risk);
+                risks
+            })
+    }
+
+    /// Returns the ids of the Things referred by the conditions, sorted and without duplicates.
+    #[must_use]
+    pub fn things(&self) -> Vec<&str> {
+        let mut things: Vec<&str> = self
+            .hazards
+            .iter()
+            .flat_map(|hazard| hazard.conditions.iter().flatten())
+            .map(|condition| &*condition.thing)
+            .collect();
+        things.sort_unstable();
+        things.dedup();
+        things
+    }
+}
+
+/// A hazard whose conditions refer to the data of multiple Things.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct CompoundHazard {
+    /// The associated risk.
+    #[serde(rename = "sho:risk")]
+    pub risk: hazard::Risk,
+
+    /// The set of conditions that are required in order to trigger the hazard.
+    ///
+    /// Like for [`Hazard::conditions`], the inner vectors express a logic `AND` between
+    /// conditions and the outer one express a logic `OR` between sets of conditions.
+    ///
+    /// [`Hazard::conditions`]: hazard::Hazard::conditions
+    #[serde(
+        rename = "sho:conditions",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub conditions: Vec<Vec<CompoundCondition>>,
+}
+
+impl CompoundHazard {
+    /// Checks whether the hazard is triggered by the states of the Things of a home.
+    ///
+    /// A hazard without any set of conditions is never triggered.
+    #[must_use]
+    pub fn is_triggered_by<K>(&self, home: &Home<K>) -> bool
+    where
+        K: Ord + Borrow<str>,
+    {
+        self.conditions
+            .iter()
+            .any(|conditions| conditions.iter().all(|cond| cond.is_satisfied_by(home)))
+    }
+}
+
+/// A condition on the data of a specific Thing.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct CompoundCondition {
+    /// The id of the Thing.
+    #[serde(rename = "sho:thing")]
+    pub thing: String,
+
+    /// The JSON pointer for the expression to be evaluated.
+    ///
+    /// The relative document is the Thing Description of [`thing`].
+    ///
+    /// [`thing`]: CompoundCondition::thing
+    #[serde(rename = "sho:pointer")]
+    pub pointer: JsonPointer,
+
+    /// The condition that needs to be satisfied.
+    #[serde(rename = "sho:condition")]
+    pub condition: condition::Condition,
+}
+
+impl CompoundCondition {
+    /// Checks whether the state of the Thing satisfies the condition.
+    ///
+    /// When the Thing is not in the home or the [`pointer`] cannot be resolved in its state, the
+    /// condition is not satisfied. See [`hazard::Condition::is_satisfied_by`] for more
+    /// information.
+    ///
+    /// [`pointer`]: CompoundCondition::pointer
+    #[must_use]
+    pub fn is_satisfied_by<K>(&self, home: &Home<K>) -> bool
+    where
+        K: Ord + Borrow<str>,
+    {
+        home.get(&*self.thing).is_some_and(|entry| {
+            entry
+                .state()
+                .resolve(&self.pointer.0)
+                .is_ok_and(|value| self.condition.is_satisfied_by(value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::Sifis;
+
+    use super::*;
+
+    fn rules() -> CompoundRules {
+        serde_json::from_value(json!({
+            "sho:hazards": [
+                {
+                    "sho:risk": { "@id": "sho:Asphyxia", "sho:level": 8 },
+                    "sho:conditions": [[
+                        { "sho:thing": "hob", "sho:pointer": "/properties/on", "sho:condition": true },
+                        { "sho:thing": "window", "sho:pointer": "/properties/open", "sho:condition": false },
+                        { "sho:thing": "extractor", "sho:pointer": "/properties/speed", "sho:condition": { "sho:value": 0, "sho:op": "le" } },
+                    ]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:Asphyxia", "sho:level": 4 },
+                    "sho:conditions": [
+                        [{ "sho:thing": "hob", "sho:pointer": "/properties/power", "sho:condition": { "sho:value": 2000, "sho:op": "gt" } }],
+                        [{ "sho:thing": "boiler", "sho:pointer": "/properties/on", "sho:condition": true }],
+                    ],
+                },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn evaluate() {
+        let rules = rules();
+        assert_eq!(rules.things(), ["boiler", "extractor", "hob", "window"]);
+
+        let mut home: Home<String> = [
+            (
+                "hob",
+                json!({ "properties": { "on": true, "power": 1500 } }),
+            ),
+            ("window", json!({ "properties": { "open": false } })),
+            ("extractor", json!({ "properties": { "speed": 2 } })),
+        ]
+        .into_iter()
+        .map(|(thing, state)| (thing.to_owned(), Sifis::default(), state))
+        .collect();
+        assert_eq!(rules.evaluate(&home), []);
+
+        home.update_state("extractor", json!({ "properties": { "speed": 0 } }));
+        assert_eq!(
+            rules.evaluate(&home),
+            [hazard::Risk {
+                id: hazard::Id::Asphyxia,
+                level: 8,
+            }],
+        );
+
+        home.remove("extractor");
+        home.update_state(
+            "hob",
+            json!({ "properties": { "on": true, "power": 2500 } }),
+        );
+        assert_eq!(
+            rules.evaluate(&home),
+            [hazard::Risk {
+                id: hazard::Id::Asphyxia,
+                level: 4,
+            }],
+        );
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let rules = rules();
+        let value = serde_json::to_value(&rules).unwrap();
+        assert_eq!(
+            value["sho:hazards"][0]["sho:conditions"][0][0]["sho:thing"],
+            "hob"
+        );
+        assert_eq!(
+            serde_json::from_value::<CompoundRules>(value).unwrap(),
+            rules
+        );
+        assert_eq!(
+            serde_json::to_value(CompoundRules::default()).unwrap(),
+            json!({})
+        );
+    }
+}
@@ -1,7 +1,8 @@
 //! A set of structures for conditional expressions and values for hazards.
 
-use std::{
-    borrow::Cow,
+use alloc::{borrow::Cow, string::String};
+use core::{
+    cmp::Ordering,
     fmt::{self, Display},
 };
 
@@ -9,6 +10,8 @@ use serde::{Deserialize, Serialize};
 
 /// A condition, as expression or value.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "ConditionKind"))]
 #[serde(untagged)]
 pub enum Condition {
     /// A simple way to express value equality.
@@ -18,8 +21,52 @@ pub enum Condition {
     Expr(Expr),
 }
 
+impl Condition {
+    /// Checks whether a JSON value satisfies the condition.
+    ///
+    /// The value must have the same type of the value of the condition in order to satisfy it,
+    /// numbers are compared by their numeric value.
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use sifis_td::condition::{Condition, Expr, Operation};
+    ///
+    /// let condition = Condition::Expr(Expr {
+    ///     value: 5.into(),
+    ///     op: Operation::Ge,
+    /// });
+    /// assert!(condition.is_satisfied_by(&json!(5.5)));
+    /// assert!(!condition.is_satisfied_by(&json!(4)));
+    /// assert!(!condition.is_satisfied_by(&json!("5")));
+    /// ```
+    #[must_use]
+    pub fn is_satisfied_by(&self, value: &serde_json::Value) -> bool {
+        let (expected, op) = match self {
+            Self::Value(expected) => (expected, None),
+            Self::Expr(Expr { value, op }) => (value, Some(*op)),
+        };
+
+        let ordering = match (value, expected) {
+            (serde_json::Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (serde_json::Value::Number(a), Value::Number(b)) => compare_numbers(a, b),
+            (serde_json::Value::String(a), Value::String(b)) => Some(a.as_str().cmp(b)),
+            _ => None,
+        };
+
+        ordering.is_some_and(|ordering| match op {
+            None => ordering.is_eq(),
+            Some(Operation::Lt) => ordering.is_lt(),
+            Some(Operation::Le) => ordering.is_le(),
+            Some(Operation::Ne) => ordering.is_ne(),
+            Some(Operation::Gt) => ordering.is_gt(),
+            Some(Operation::Ge) => ordering.is_ge(),
+        })
+    }
+}
+
 /// A value to be used for conditions.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Value {
     /// A boolean value.
@@ -32,6 +79,113 @@ pub enum Value {
     String(Cow<'static, str>),
 }
 
+impl Value {
+    /// Compares two values of the same type.
+    ///
+    /// Numbers are compared by their numeric value, independently from their JSON representation
+    /// (i.e.: `3` and `3.0` are equal). Returns `None` when the values have different types.
+    pub(crate) fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => Some(a.cmp(b)),
+            (Self::Number(a), Self::Number(b)) => compare_numbers(a, b),
+            (Self::String(a), Self::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn compare_numbers(a: &serde_json::Number, b: &serde_json::Number) -> Option<Ordering> {
+    ComparableNumber::from(a).partial_cmp(&ComparableNumber::from(b))
+}
+
+/// A JSON number in a representation that can be compared cheaply.
+///
+/// Numbers are compared as unsigned integers when possible, then as signed integers, and
+/// finally as floats.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ComparableNumber {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+impl ComparableNumber {
+    #[inline]
+    fn as_i64(self) -> Option<i64> {
+        match self {
+            Self::Unsigned(value) => i64::try_from(value).ok(),
+            Self::Signed(value) => Some(value),
+            Self::Float(_) => None,
+        }
+    }
+
+    #[inline]
+    #[allow(clippy::cast_precision_loss)]
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Unsigned(value) => value as f64,
+            Self::Signed(value) => value as f64,
+            Self::Float(value) => value,
+        }
+    }
+}
+
+impl From<&serde_json::Number> for ComparableNumber {
+    #[inline]
+    fn from(value: &serde_json::Number) -> Self {
+        if let Some(value) = value.as_u64() {
+            Self::Unsigned(value)
+        } else if let Some(value) = value.as_i64() {
+            Self::Signed(value)
+        } else {
+            Self::Float(value.as_f64().unwrap_or(f64::NAN))
+        }
+    }
+}
+
+impl PartialEq for ComparableNumber {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for ComparableNumber {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if let (Self::Unsigned(a), Self::Unsigned(b)) = (self, other) {
+            return Some(a.cmp(b));
+        }
+
+        if let Some((a, b)) = self.as_i64().zip(other.as_i64()) {
+            return Some(a.cmp(&b));
+        }
+
+        self.as_f64().partial_cmp(&other.as_f64())
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => Display::fmt(value, f),
+            Self::Number(value) => Display::fmt(value, f),
+            Self::String(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+impl From<Value> for serde_json::Value {
+    #[inline]
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Bool(value) => Self::Bool(value),
+            Value::Number(value) => Self::Number(value),
+            Value::String(value) => Self::String(value.into_owned()),
+        }
+    }
+}
+
 impl From<bool> for Value {
     #[inline]
     fn from(value: bool) -> Self {
@@ -87,6 +241,7 @@ impl Display for InvalidFloat {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for InvalidFloat {}
 
 impl TryFrom<f32> for Value {
@@ -111,6 +266,7 @@ impl TryFrom<f64> for Value {
 
 /// A conditional expression.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Expr {
     /// The value to be compared.
     #[serde(rename = "sho:value")]
@@ -125,6 +281,7 @@ pub struct Expr {
 
 /// A conditional operation.
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Operation {
     /// Less than.
@@ -142,3 +299,29 @@ pub enum Operation {
     /// Greater or equal than.
     Ge,
 }
+
+impl Operation {
+    /// The name of the operation in Thing Descriptions, i.e. `ge`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lt => "lt",
+            Self::Le => "le",
+            Self::Ne => "ne",
+            Self::Gt => "gt",
+            Self::Ge => "ge",
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        })
+    }
+}
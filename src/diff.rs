@@ -0,0 +1,640 @@
+//! Semantic differences between two versions of a [`Sifis`] extension.
+//!
+//! When the Thing Description of a device changes (i.e.: after a firmware update), it is
+//! necessary to know how the hazards have been affected. The [`diff`] function compares two
+//! versions of a [`Sifis`] extension and returns a [`Report`] with:
+//!
+//! - the hazards that have been added or removed;
+//! - the changes of the maximum level for each [`Id`];
+//! - the changes of the conditions for hazards with the same [`Id`] and level, including the
+//!   modified thresholds and whether the new conditions are weaker or stronger than the old ones.
+//!
+//! Conditions are compared by their [canonical form], therefore differences that do not change
+//! the semantic of the conditions (like the order of the clauses) are not reported.
+//!
+//! The report can be serialized and it implements [`Display`] to obtain a human-readable form.
+//!
+//! ```
+//! use sifis_td::{diff, hazard, Sifis};
+//!
+//! let old = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(80))
+//!     .build();
+//! let new = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(60))
+//!     .take_pictures(2, |cond| cond.when("/properties/on").eq(true))
+//!     .build();
+//!
+//! let report = diff(&old, &new);
+//! assert_eq!(report.added[0].id, hazard::Id::TakePictures);
+//! assert_eq!(report.changed[0].conditions[0].strength, diff::Strength::Weaker);
+//! ```
+//!
+//! [`Id`]: hazard::Id
+//! [`Display`]: std::fmt::Display
+//! [canonical form]: hazard::Hazard::canonicalize
+
+use std::{
+    fmt::{self, Display},
+    ops::Not,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{condition, domain, hazard, Sifis};
+
+/// The differences between two versions of a [`Sifis`] extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    /// The hazards with an [`Id`] that is only available in the new version.
+    ///
+    /// [`Id`]: hazard::Id
+    pub added: Vec<hazard::Risk>,
+
+    /// The hazards with an [`Id`] that is only available in the old version.
+    ///
+    /// [`Id`]: hazard::Id
+    pub removed: Vec<hazard::Risk>,
+
+    /// The changes of the hazards with an [`Id`] available in both versions.
+    ///
+    /// [`Id`]: hazard::Id
+    pub changed: Vec<Change>,
+}
+
+impl Report {
+    /// Returns `true` if there are no differences between the two versions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The changes for the hazards with a specific [`Id`].
+///
+/// [`Id`]: hazard::Id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    /// The id of the hazards.
+    pub id: hazard::Id,
+
+    /// The change of the maximum level among the hazards, if any.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_level: Option<LevelChange>,
+
+    /// The levels that are only available in the new version.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub added_levels: Vec<u8>,
+
+    /// The levels that are only available in the old version.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub removed_levels: Vec<u8>,
+
+    /// The changes of the conditions for the levels available in both versions.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub conditions: Vec<ConditionsChange>,
+}
+
+/// A change of a risk level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LevelChange {
+    /// The level in the old version.
+    pub old: u8,
+
+    /// The level in the new version.
+    pub new: u8,
+}
+
+impl LevelChange {
+    /// Returns `true` if the level has been increased.
+    #[inline]
+    #[must_use]
+    pub fn is_increase(&self) -> bool {
+        self.new > self.old
+    }
+}
+
+/// A change of the conditions for the hazards with the same [`Id`] and level.
+///
+/// When multiple hazards share the same [`Id`] and level, their conditions are combined with a
+/// logic `OR`.
+///
+/// [`Id`]: hazard::Id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConditionsChange {
+    /// The level of the hazards.
+    pub level: u8,
+
+    /// The relation between the new conditions and the old ones.
+    pub strength: Strength,
+
+    /// The expressions with the same JSON pointer and operation but a different value.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub thresholds: Vec<ThresholdChange>,
+}
+
+/// The relation between the new conditions of a hazard and the old ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strength {
+    /// The conditions are satisfied by the same states, even if they are expressed differently.
+    Equivalent,
+
+    /// The new conditions are satisfied by more states, the hazard can be triggered more easily.
+    Weaker,
+
+    /// The new conditions are satisfied by less states, the hazard is triggered less easily.
+    Stronger,
+
+    /// The conditions cannot be compared.
+    ///
+    /// This happens when each version is satisfied by some states that do not satisfy the other,
+    /// or when the analysis is not able to prove any relation between them.
+    Incomparable,
+}
+
+/// A modified value in a conditional expression.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdChange {
+    /// The JSON pointer of the expression.
+    pub pointer: hazard::JsonPointer,
+
+    /// The operation of the expression.
+    pub op: condition::Operation,
+
+    /// The value in the old version.
+    pub old: condition::Value,
+
+    /// The value in the new version.
+    pub new: condition::Value,
+}
+
+/// Computes the semantic differences between two versions of a [`Sifis`] extension.
+///
+/// See the [module documentation](self) for more information.
+#[must_use]
+pub fn diff(old: &Sifis, new: &Sifis) -> Report {
+    let old_groups = Group::from_sifis(old);
+    let new_groups = Group::from_sifis(new);
+
+    let mut report = Report::default();
+    for new_group in &new_groups {
+        match old_groups.iter().find(|group| group.id == new_group.id) {
+            Some(old_group) => {
+                if let Some(change) = old_group.diff(new_group) {
+                    report.changed.push(change);
+                }
+            }
+            None => report.added.extend(new_group.risks()),
+        }
+    }
+
+    for old_group in &old_groups {
+        if new_groups.iter().all(|group| group.id != old_group.id) {
+            report.removed.extend(old_group.risks());
+        }
+    }
+
+    report
+}
+
+/// The hazards with the same id, grouped by level.
+struct Group<'a> {
+    id: hazard::Id,
+    levels: Vec<(u8, Vec<&'a [hazard::Condition]>)>,
+}
+
+impl<'a> Group<'a> {
+    fn from_sifis(sifis: &'a Sifis) -> Vec<Self> {
+        let mut groups: Vec<Self> = Vec::new();
+        for hazard in &sifis.hazards {
+            let hazard::Risk { id, level } = hazard.risk;
+            let index = groups
+                .iter()
+                .position(|group| group.id == id)
+                .unwrap_or_else(|| {
+                    groups.push(Group {
+                        id,
+                        levels: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+
+            let group = &mut groups[index];
+            let conditions = hazard.conditions.iter().map(Vec::as_slice);
+            match group.levels.iter_mut().find(|(cur, _)| *cur == level) {
+                Some((_, branches)) => branches.extend(conditions),
+                None => group.levels.push((level, conditions.collect())),
+            }
+        }
+
+        groups
+    }
+
+    fn risks(&self) -> impl Iterator<Item = hazard::Risk> + '_ {
+        self.levels
+            .iter()
+            .map(|&(level, _)| hazard::Risk { id: self.id, level })
+    }
+
+    fn max_level(&self) -> u8 {
+        self.levels
+            .iter()
+            .map(|&(level, _)| level)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn diff(&self, new: &Self) -> Option<Change> {
+        let max_level = Some(LevelChange {
+            old: self.max_level(),
+            new: new.max_level(),
+        })
+        .filter(|change| change.old != change.new);
+
+        let added_levels = new
+            .levels
+            .iter()
+            .map(|&(level, _)| level)
+            .filter(|&level| self.levels.iter().all(|&(cur, _)| cur != level))
+            .collect();
+
+        let removed_levels = self
+            .levels
+            .iter()
+            .map(|&(level, _)| level)
+            .filter(|&level| new.levels.iter().all(|&(cur, _)| cur != level))
+            .collect();
+
+        let conditions = new
+            .levels
+            .iter()
+            .filter_map(|(level, new_branches)| {
+                let (_, old_branches) = self.levels.iter().find(|(cur, _)| cur == level)?;
+                diff_conditions(*level, old_branches, new_branches)
+            })
+            .collect();
+
+        let change = Change {
+            id: self.id,
+            max_level,
+            added_levels,
+            removed_levels,
+            conditions,
+        };
+
+        (change.max_level.is_some()
+            || change.added_levels.is_empty().not()
+            || change.removed_levels.is_empty().not()
+            || change.conditions.is_empty().not())
+        .then_some(change)
+    }
+}
+
+fn diff_conditions(
+    level: u8,
+    old: &[&[hazard::Condition]],
+    new: &[&[hazard::Condition]],
+) -> Option<ConditionsChange> {
+    let collect = |branches: &[&[hazard::Condition]]| {
+        branches
+            .iter()
+            .map(|branch| branch.to_vec())
+            .collect::<Vec<_>>()
+    };
+
+    // Cosmetic differences, like a different order of the clauses, are not changes.
+    let old = collect(old);
+    let new = collect(new);
+    if domain::canonicalize(&old) == domain::canonicalize(&new) {
+        return None;
+    }
+
+    let strength = match (domain::implies(&old, &new), domain::implies(&new, &old)) {
+        (true, true) => Strength::Equivalent,
+        (true, false) => Strength::Weaker,
+        (false, true) => Strength::Stronger,
+        (false, false) => Strength::Incomparable,
+    };
+
+    let thresholds = thresholds(&old, &new);
+    Some(ConditionsChange {
+        level,
+        strength,
+        thresholds,
+    })
+}
+
+/// Finds the expressions with the same pointer and operation, but with a different value.
+///
+/// Only the expressions appearing once for each version are considered, otherwise it is not
+/// possible to tell which ones have been changed.
+fn thresholds(
+    old: &[Vec<hazard::Condition>],
+    new: &[Vec<hazard::Condition>],
+) -> Vec<ThresholdChange> {
+    fn exprs(
+        conditions: &[Vec<hazard::Condition>],
+    ) -> impl Iterator<Item = (&hazard::JsonPointer, &condition::Expr)> {
+        conditions
+            .iter()
+            .flatten()
+            .filter_map(|cond| match &cond.condition {
+                condition::Condition::Expr(expr) => Some((&cond.pointer, expr)),
+                condition::Condition::Value(_) => None,
+            })
+    }
+
+    fn unique<'a>(
+        conditions: &'a [Vec<hazard::Condition>],
+        pointer: &hazard::JsonPointer,
+        op: condition::Operation,
+    ) -> Option<&'a condition::Value> {
+        let mut iter = exprs(conditions).filter(|(cur, expr)| *cur == pointer && expr.op == op);
+        let (_, expr) = iter.next()?;
+        iter.next().is_none().then_some(&expr.value)
+    }
+
+    exprs(new)
+        .filter_map(|(pointer, expr)| {
+            let new_value = unique(new, pointer, expr.op)?;
+            let old_value = unique(old, pointer, expr.op)?;
+
+            (old_value != new_value).then(|| ThresholdChange {
+                pointer: pointer.clone(),
+                op: expr.op,
+                old: old_value.clone(),
+                new: new_value.clone(),
+            })
+        })
+        .collect()
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No changes\n");
+        }
+
+        for risk in &self.added {
+            writeln!(f, "+ {} (level {})", risk.id.risk().name, risk.level)?;
+        }
+
+        for risk in &self.removed {
+            writeln!(f, "- {} (level {})", risk.id.risk().name, risk.level)?;
+        }
+
+        for change in &self.changed {
+            let name = change.id.risk().name;
+            writeln!(f, "~ {name}")?;
+
+            if let Some(LevelChange { old, new }) = change.max_level {
+                let verb = if new > old { "increased" } else { "decreased" };
+                writeln!(f, "    maximum level {verb} from {old} to {new}")?;
+            }
+
+            for level in &change.added_levels {
+                writeln!(f, "    + level {level}")?;
+            }
+
+            for level in &change.removed_levels {
+                writeln!(f, "    - level {level}")?;
+            }
+
+            for conditions in &change.conditions {
+                let strength = match conditions.strength {
+                    Strength::Equivalent => "equivalent",
+                    Strength::Weaker => "weaker",
+                    Strength::Stronger => "stronger",
+                    Strength::Incomparable => "incomparable",
+                };
+                writeln!(
+                    f,
+                    "    level {}: conditions are {strength}",
+                    conditions.level
+                )?;
+
+                for threshold in &conditions.thresholds {
+                    writeln!(
+                        f,
+                        "        {}: {op} {} -> {op} {}",
+                        &*threshold.pointer,
+                        threshold.old,
+                        threshold.new,
+                        op = threshold.op,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn no_changes() {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/on").eq(true))
+            .build();
+
+        let report = diff(&sifis, &sifis);
+        assert!(report.is_empty());
+        assert_eq!(report.to_string(), "No changes\n");
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let old = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/on").eq(true))
+            .explosion(1, |cond| cond.when("/properties/on").eq(true))
+            .build();
+        let new = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/on").eq(true))
+            .take_pictures(2, |cond| cond.when("/properties/on").eq(true))
+            .build();
+
+        let report = diff(&old, &new);
+        assert_eq!(
+            report,
+            Report {
+                added: vec![hazard::Risk {
+                    id: hazard::Id::TakePictures,
+                    level: 2,
+                }],
+                removed: vec![hazard::Risk {
+                    id: hazard::Id::Explosion,
+                    level: 1,
+                }],
+                changed: Vec::new(),
+            },
+        );
+        assert_eq!(
+            report.to_string(),
+            "+ Take pictures (level 2)\n- Explosion (level 1)\n",
+        );
+    }
+
+    #[test]
+    fn level_changes() {
+        let old = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/level").ge(5))
+            .fire_hazard(5, |cond| cond.when("/properties/level").ge(8))
+            .build();
+        let new = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/level").ge(5))
+            .fire_hazard(7, |cond| cond.when("/properties/level").ge(8))
+            .build();
+
+        let report = diff(&old, &new);
+        assert_eq!(
+            report.changed,
+            [Change {
+                id: hazard::Id::FireHazard,
+                max_level: Some(LevelChange { old: 5, new: 7 }),
+                added_levels: vec![7],
+                removed_levels: vec![5],
+                conditions: Vec::new(),
+            }],
+        );
+        assert!(report.changed[0].max_level.unwrap().is_increase());
+    }
+
+    #[test]
+    fn condition_strength_ignores_rewritten_conditions() {
+        let old = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(5)
+                    .and("/properties/on")
+                    .eq(true)
+            })
+            .explosion(1, |cond| cond.when("/properties/level").gt(9))
+            .scald(1, |cond| cond.when("/properties/level").gt(9))
+            .burn(1, |cond| cond.when("/properties/level").gt(9))
+            .build();
+        let new = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/level").ge(3))
+            .explosion(1, |cond| {
+                cond.when("/properties/level")
+                    .gt(9)
+                    .and("/properties/on")
+                    .eq(true)
+            })
+            .scald(1, |cond| cond.when("/properties/on").eq(true))
+            // The same conditions in a different form, which is not reported as a change.
+            .burn(1, |cond| {
+                cond.when("/properties/level")
+                    .ne(9)
+                    .and("/properties/level")
+                    .ge(9)
+            })
+            .build();
+
+        let strengths: Vec<_> = diff(&old, &new)
+            .changed
+            .into_iter()
+            .map(|change| (change.id, change.conditions[0].strength))
+            .collect();
+
+        assert_eq!(
+            strengths,
+            [
+                (hazard::Id::FireHazard, Strength::Weaker),
+                (hazard::Id::Explosion, Strength::Stronger),
+                (hazard::Id::Scald, Strength::Incomparable),
+            ],
+        );
+    }
+
+    #[test]
+    fn equivalent_conditions() {
+        // Integers above 2^53 are not normalized, so the canonical forms differ.
+        let old = Sifis::builder()
+            .burn(1, |cond| cond.when("/properties/level").ge(1e16))
+            .build();
+        let new = Sifis::builder()
+            .burn(1, |cond| {
+                cond.when("/properties/level")
+                    .ge(10_000_000_000_000_000_u64)
+            })
+            .build();
+
+        let report = diff(&old, &new);
+        assert_eq!(
+            report.changed[0].conditions[0].strength,
+            Strength::Equivalent
+        );
+        assert_eq!(
+            report.to_string(),
+            "~ Burn\n    level 1: conditions are equivalent\n        \
+             /properties/level: >= 1e+16 -> >= 10000000000000000\n",
+        );
+    }
+
+    #[test]
+    fn threshold_changes() {
+        let old = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(5)
+                    .and("/properties/level")
+                    .lt(10)
+            })
+            .build();
+        let new = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(5)
+                    .and("/properties/level")
+                    .lt(20)
+            })
+            .build();
+
+        let report = diff(&old, &new);
+        assert_eq!(
+            report.changed[0].conditions,
+            [ConditionsChange {
+                level: 3,
+                strength: Strength::Weaker,
+                thresholds: vec![ThresholdChange {
+                    pointer: "/properties/level".try_into().unwrap(),
+                    op: condition::Operation::Lt,
+                    old: 10.into(),
+                    new: 20.into(),
+                }],
+            }],
+        );
+
+        assert_eq!(
+            report.to_string(),
+            "~ Fire hazard\n    level 3: conditions are weaker\n        /properties/level: < 10 -> < 20\n",
+        );
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "added": [],
+                "removed": [],
+                "changed": [{
+                    "id": "sho:FireHazard",
+                    "conditions": [{
+                        "level": 3,
+                        "strength": "weaker",
+                        "thresholds": [{
+                            "pointer": "/properties/level",
+                            "op": "lt",
+                            "old": 10,
+                            "new": 20,
+                        }],
+                    }],
+                }],
+            }),
+        );
+    }
+}
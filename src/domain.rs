@@ -0,0 +1,790 @@
+//! An interval-based abstraction over hazard conditions.
+//!
+//! Each [`hazard::Condition`] restricts the values that the data behind its JSON pointer can
+//! assume. The restriction can be represented as a [`Range`], and the intersection of all the
+//! ranges for the same pointer inside a logic `AND` of conditions gives a [`Branch`].
+//!
+//! The conditions are evaluated in the same way for every kind of value: a value of a different
+//! type never satisfies a condition. Numbers and strings are considered _dense_, which means that
+//! an interval is never empty unless its bounds cross.
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::{cmp::Ordering, ops::Bound, ops::Not};
+
+use crate::{
+    condition::{self, Operation, Value},
+    hazard,
+};
+
+/// The type of the values contained in a [`Range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Bool,
+    Number,
+    String,
+}
+
+impl Kind {
+    pub(crate) fn of(value: &Value) -> Self {
+        match value {
+            Value::Bool(_) => Self::Bool,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+        }
+    }
+}
+
+/// A non-empty set of values of the same [`Kind`].
+///
+/// A range is always kept in a normalized form: excluded values are strictly inside the bounds and
+/// the bounds of boolean ranges are always inclusive.
+#[derive(Debug, Clone)]
+pub(crate) struct Range {
+    pub(crate) kind: Kind,
+    pub(crate) lower: Bound<Value>,
+    pub(crate) upper: Bound<Value>,
+    pub(crate) excluded: Vec<Value>,
+}
+
+impl Range {
+    /// Creates the range of the values satisfying the condition.
+    ///
+    /// Returns `None` if no value satisfies the condition, like in `x < false`.
+    pub(crate) fn from_condition(condition: &condition::Condition) -> Option<Self> {
+        let (value, op) = match condition {
+            condition::Condition::Value(value) => {
+                return Self::point(normalize_value(value)).normalize();
+            }
+            condition::Condition::Expr(condition::Expr { value, op }) => {
+                (normalize_value(value), *op)
+            }
+        };
+
+        let kind = Kind::of(&value);
+        let (lower, upper, excluded) = match op {
+            Operation::Lt => (Bound::Unbounded, Bound::Excluded(value), Vec::new()),
+            Operation::Le => (Bound::Unbounded, Bound::Included(value), Vec::new()),
+            Operation::Gt => (Bound::Excluded(value), Bound::Unbounded, Vec::new()),
+            Operation::Ge => (Bound::Included(value), Bound::Unbounded, Vec::new()),
+            Operation::Ne => (Bound::Unbounded, Bound::Unbounded, vec![value]),
+        };
+
+        Self {
+            kind,
+            lower,
+            upper,
+            excluded,
+        }
+        .normalize()
+    }
+
+    #[cfg(feature = "std")]
+    /// Creates a range from its bounds, returning `None` if the range is empty.
+    pub(crate) fn new(kind: Kind, lower: Bound<Value>, upper: Bound<Value>) -> Option<Self> {
+        Self {
+            kind,
+            lower: lower.map(|value| normalize_value(&value)),
+            upper: upper.map(|value| normalize_value(&value)),
+            excluded: Vec::new(),
+        }
+        .normalize()
+    }
+
+    #[cfg(feature = "std")]
+    /// Creates the range containing all the values of a kind.
+    pub(crate) fn full(kind: Kind) -> Self {
+        let (lower, upper) = if kind == Kind::Bool {
+            (
+                Bound::Included(Value::Bool(false)),
+                Bound::Included(Value::Bool(true)),
+            )
+        } else {
+            (Bound::Unbounded, Bound::Unbounded)
+        };
+
+        Self {
+            kind,
+            lower,
+            upper,
+            excluded: Vec::new(),
+        }
+    }
+
+    /// Creates a range containing a single value.
+    pub(crate) fn point(value: Value) -> Self {
+        Self {
+            kind: Kind::of(&value),
+            lower: Bound::Included(value.clone()),
+            upper: Bound::Included(value),
+            excluded: Vec::new(),
+        }
+    }
+
+    /// Returns the single value contained in the range, if any.
+    pub(crate) fn as_point(&self) -> Option<&Value> {
+        match (&self.lower, &self.upper) {
+            (Bound::Included(a), Bound::Included(b)) if a.compare(b) == Some(Ordering::Equal) => {
+                Some(a)
+            }
+            _ => None,
+        }
+    }
+
+    /// Expresses the range as a minimal set of conditions.
+    pub(crate) fn to_conditions(&self) -> Vec<condition::Condition> {
+        if let Some(value) = self.as_point() {
+            return vec![condition::Condition::Value(value.clone())];
+        }
+
+        let expr = |value: &Value, op| {
+            condition::Condition::Expr(condition::Expr {
+                value: value.clone(),
+                op,
+            })
+        };
+
+        let mut conditions = Vec::with_capacity(self.excluded.len() + 2);
+        match &self.lower {
+            Bound::Included(value) => conditions.push(expr(value, Operation::Ge)),
+            Bound::Excluded(value) => conditions.push(expr(value, Operation::Gt)),
+            Bound::Unbounded => {}
+        }
+
+        // A full boolean range is not expressed by any bound, but the type must be preserved.
+        if self.kind != Kind::Bool {
+            match &self.upper {
+                Bound::Included(value) => conditions.push(expr(value, Operation::Le)),
+                Bound::Excluded(value) => conditions.push(expr(value, Operation::Lt)),
+                Bound::Unbounded => {}
+            }
+        }
+
+        conditions.extend(self.excluded.iter().map(|value| expr(value, Operation::Ne)));
+        conditions
+    }
+
+    /// Compares two ranges in order to obtain a deterministic sorting.
+    fn sorting_cmp(&self, other: &Self) -> Ordering {
+        (self.kind as u8)
+            .cmp(&(other.kind as u8))
+            .then_with(|| cmp_lower(&self.lower, &other.lower))
+            .then_with(|| cmp_upper(&self.upper, &other.upper))
+            .then_with(|| {
+                self.excluded
+                    .iter()
+                    .zip(&other.excluded)
+                    .map(|(a, b)| a.compare(b).unwrap_or(Ordering::Equal))
+                    .find(|ord| ord.is_ne())
+                    .unwrap_or_else(|| self.excluded.len().cmp(&other.excluded.len()))
+            })
+    }
+
+    /// Intersects two ranges, returning `None` if the result is empty.
+    pub(crate) fn intersect(&self, other: &Self) -> Option<Self> {
+        if self.kind != other.kind {
+            return None;
+        }
+
+        let lower = if cmp_lower(&self.lower, &other.lower).is_ge() {
+            self.lower.clone()
+        } else {
+            other.lower.clone()
+        };
+
+        let upper = if cmp_upper(&self.upper, &other.upper).is_le() {
+            self.upper.clone()
+        } else {
+            other.upper.clone()
+        };
+
+        let excluded = self
+            .excluded
+            .iter()
+            .chain(&other.excluded)
+            .cloned()
+            .collect();
+
+        Self {
+            kind: self.kind,
+            lower,
+            upper,
+            excluded,
+        }
+        .normalize()
+    }
+
+    /// Checks whether all the values of `self` are contained in `other`.
+    pub(crate) fn is_subset(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && cmp_lower(&self.lower, &other.lower).is_ge()
+            && cmp_upper(&self.upper, &other.upper).is_le()
+            && other
+                .excluded
+                .iter()
+                .all(|value| self.contains(value).not())
+    }
+
+    /// Checks whether the value is contained in the range.
+    pub(crate) fn contains(&self, value: &Value) -> bool {
+        Kind::of(value) == self.kind
+            && self.within_bounds(value)
+            && self
+                .excluded
+                .iter()
+                .all(|excluded| excluded.compare(value) != Some(Ordering::Equal))
+    }
+
+    fn within_bounds(&self, value: &Value) -> bool {
+        let lower = match &self.lower {
+            Bound::Included(bound) => bound.compare(value).is_some_and(Ordering::is_le),
+            Bound::Excluded(bound) => bound.compare(value).is_some_and(Ordering::is_lt),
+            Bound::Unbounded => true,
+        };
+
+        let upper = match &self.upper {
+            Bound::Included(bound) => bound.compare(value).is_some_and(Ordering::is_ge),
+            Bound::Excluded(bound) => bound.compare(value).is_some_and(Ordering::is_gt),
+            Bound::Unbounded => true,
+        };
+
+        lower && upper
+    }
+
+    fn normalize(mut self) -> Option<Self> {
+        if self.kind == Kind::Bool {
+            let mut allowed = [false, true]
+                .into_iter()
+                .map(Value::Bool)
+                .filter(|value| self.contains(value));
+
+            let lower = allowed.next()?;
+            let upper = allowed.next().unwrap_or_else(|| lower.clone());
+            return Some(Self {
+                kind: Kind::Bool,
+                lower: Bound::Included(lower),
+                upper: Bound::Included(upper),
+                excluded: Vec::new(),
+            });
+        }
+
+        let mut excluded = core::mem::take(&mut self.excluded);
+        excluded.retain(|value| Kind::of(value) == self.kind && self.within_bounds(value));
+        excluded.sort_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal));
+        excluded.dedup_by(|a, b| a.compare(b) == Some(Ordering::Equal));
+
+        if let Bound::Included(lower) = &self.lower {
+            if excluded
+                .first()
+                .is_some_and(|value| value.compare(lower) == Some(Ordering::Equal))
+            {
+                self.lower = Bound::Excluded(excluded.remove(0));
+            }
+        }
+
+        if let Bound::Included(upper) = &self.upper {
+            if excluded
+                .last()
+                .is_some_and(|value| value.compare(upper) == Some(Ordering::Equal))
+            {
+                self.upper = Bound::Excluded(excluded.pop()?);
+            }
+        }
+
+        if let (
+            Bound::Included(lower) | Bound::Excluded(lower),
+            Bound::Included(upper) | Bound::Excluded(upper),
+        ) = (&self.lower, &self.upper)
+        {
+            match lower.compare(upper)? {
+                Ordering::Less => {}
+                Ordering::Equal
+                    if matches!(
+                        (&self.lower, &self.upper),
+                        (Bound::Included(_), Bound::Included(_))
+                    ) => {}
+                Ordering::Equal | Ordering::Greater => return None,
+            }
+        }
+
+        self.excluded = excluded;
+        Some(self)
+    }
+}
+
+/// Expresses integral floating point numbers as integers.
+fn normalize_value(value: &Value) -> Value {
+    // Integers up to 2^53 are exactly representable as `f64`.
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.;
+
+    match value {
+        Value::Number(number) if number.is_f64() => number
+            .as_f64()
+            .filter(|&number| is_integral(number) && number.abs() <= MAX_EXACT)
+            .map_or_else(
+                || value.clone(),
+                #[allow(clippy::cast_possible_truncation)]
+                |number| Value::from(number as i64),
+            ),
+        _ => value.clone(),
+    }
+}
+
+/// Compares two lower bounds, where the greater is the most restrictive.
+pub(crate) fn cmp_lower(a: &Bound<Value>, b: &Bound<Value>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => x
+            .compare(y)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| matches!(a, Bound::Excluded(_)).cmp(&matches!(b, Bound::Excluded(_)))),
+    }
+}
+
+/// Compares two upper bounds, where the lesser is the most restrictive.
+pub(crate) fn cmp_upper(a: &Bound<Value>, b: &Bound<Value>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => x
+            .compare(y)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| matches!(a, Bound::Included(_)).cmp(&matches!(b, Bound::Included(_)))),
+    }
+}
+
+/// The ranges of the values satisfying a logic `AND` of conditions, indexed by JSON pointer.
+///
+/// JSON pointers without any condition are not included, because they can assume any value.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Branch(pub(crate) BTreeMap<hazard::JsonPointer, Range>);
+
+impl Branch {
+    /// Creates a branch from a set of conditions.
+    ///
+    /// Returns `None` if the conditions cannot be satisfied at the same time.
+    pub(crate) fn from_conditions<'a, I>(conditions: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a hazard::Condition>,
+    {
+        let mut ranges = BTreeMap::<hazard::JsonPointer, Range>::new();
+        for condition in conditions {
+            let range = Range::from_condition(&condition.condition)?;
+            let range = match ranges.get(&condition.pointer) {
+                Some(cur) => cur.intersect(&range)?,
+                None => range,
+            };
+            ranges.insert(condition.pointer.clone(), range);
+        }
+
+        Some(Self(ranges))
+    }
+
+    /// Checks whether every state satisfying `self` also satisfies `other`.
+    pub(crate) fn implies(&self, other: &Self) -> bool {
+        other
+            .0
+            .iter()
+            .all(|(pointer, range)| self.0.get(pointer).is_some_and(|cur| cur.is_subset(range)))
+    }
+}
+
+impl Branch {
+    /// Expresses the branch as a minimal set of conditions, sorted by JSON pointer.
+    pub(crate) fn to_conditions(&self) -> Vec<hazard::Condition> {
+        self.0
+            .iter()
+            .flat_map(|(pointer, range)| {
+                range
+                    .to_conditions()
+                    .into_iter()
+                    .map(|condition| hazard::Condition {
+                        pointer: pointer.clone(),
+                        condition,
+                    })
+            })
+            .collect()
+    }
+
+    /// Compares two branches in order to obtain a deterministic sorting.
+    fn sorting_cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|((pointer_a, range_a), (pointer_b, range_b))| {
+                pointer_a
+                    .cmp(pointer_b)
+                    .then_with(|| range_a.sorting_cmp(range_b))
+            })
+            .find(|ord| ord.is_ne())
+            .unwrap_or_else(|| self.0.len().cmp(&other.0.len()))
+    }
+}
+
+/// Rewrites a logic `OR` of sets of conditions in a canonical form.
+///
+/// See [`Hazard::canonicalize`] for more information.
+///
+/// [`Hazard::canonicalize`]: crate::hazard::Hazard::canonicalize
+pub(crate) fn canonicalize(conditions: &[Vec<hazard::Condition>]) -> Vec<Vec<hazard::Condition>> {
+    let mut branches: Vec<Branch> = Vec::with_capacity(conditions.len());
+    for branch in conditions.iter().filter_map(Branch::from_conditions) {
+        if branches.iter().any(|cur| branch.implies(cur)) {
+            continue;
+        }
+
+        branches.retain(|cur| cur.implies(&branch).not());
+        branches.push(branch);
+    }
+
+    branches.sort_by(Branch::sorting_cmp);
+    branches.iter().map(Branch::to_conditions).collect()
+}
+
+/// Checks whether a logic `OR` of sets of conditions implies another one.
+///
+/// The check is conservative: each branch of `a` must imply at least one branch of `b`. This means
+/// that `false` can be returned for some cases in which the implication holds, i.e. when a branch
+/// of `a` is covered by the union of multiple branches of `b`.
+pub(crate) fn implies(a: &[Vec<hazard::Condition>], b: &[Vec<hazard::Condition>]) -> bool {
+    let b: Vec<_> = b.iter().filter_map(Branch::from_conditions).collect();
+    a.iter()
+        .filter_map(Branch::from_conditions)
+        .all(|branch| b.iter().any(|other| branch.implies(other)))
+}
+
+#[cfg(feature = "std")]
+/// The set of values that the data behind a JSON pointer can assume, as described by a schema.
+#[derive(Debug, Clone)]
+pub(crate) struct Domain {
+    pub(crate) range: Range,
+    pub(crate) integer: bool,
+    pub(crate) values: Option<Vec<Value>>,
+}
+
+#[cfg(feature = "std")]
+impl Domain {
+    /// Creates a domain, returning `None` if no value satisfies the constraints.
+    pub(crate) fn new(range: Range, integer: bool, values: Option<Vec<Value>>) -> Option<Self> {
+        let range = if integer {
+            tighten_integer(range)?
+        } else {
+            range
+        };
+
+        let values = values.map(|values| {
+            values
+                .into_iter()
+                .filter(|value| range.contains(value) && (integer.not() || is_integer(value)))
+                .collect::<Vec<_>>()
+        });
+
+        if values.as_ref().is_some_and(Vec::is_empty) {
+            return None;
+        }
+
+        Some(Self {
+            range,
+            integer,
+            values,
+        })
+    }
+
+    /// Restricts the range to the values of the domain, returning `None` if the result is empty.
+    pub(crate) fn restrict(&self, range: &Range) -> Option<Range> {
+        let range = range.intersect(&self.range)?;
+        let range = if self.integer {
+            tighten_integer(range)?
+        } else {
+            range
+        };
+
+        match &self.values {
+            Some(values) if values.iter().all(|value| range.contains(value).not()) => None,
+            _ => Some(range),
+        }
+    }
+
+    /// Checks whether the value is part of the domain.
+    pub(crate) fn allows(&self, value: &Value) -> bool {
+        self.range.contains(value)
+            && (self.integer.not() || is_integer(value))
+            && self.values.as_ref().is_none_or(|values| {
+                values
+                    .iter()
+                    .any(|cur| cur.compare(value) == Some(Ordering::Equal))
+            })
+    }
+
+    /// Checks whether all the values of the domain are contained in the range.
+    pub(crate) fn is_covered_by(&self, range: &Range) -> bool {
+        match &self.values {
+            Some(values) => values.iter().all(|value| range.contains(value)),
+            None => self.range.is_subset(range),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Restricts the bounds of a numeric range to the integers it contains.
+///
+/// Returns `None` if the range does not contain any integer.
+fn tighten_integer(mut range: Range) -> Option<Range> {
+    if range.kind != Kind::Number {
+        return Some(range);
+    }
+
+    // Each iteration can only remove an excluded value, therefore this loop always ends.
+    loop {
+        let lower = match &range.lower {
+            Bound::Included(value) => Bound::Included(value_from_f64(ceil(as_f64(value)))?),
+            Bound::Excluded(value) => Bound::Included(value_from_f64(floor(as_f64(value)) + 1.)?),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let upper = match &range.upper {
+            Bound::Included(value) => Bound::Included(value_from_f64(floor(as_f64(value)))?),
+            Bound::Excluded(value) => Bound::Included(value_from_f64(ceil(as_f64(value)) - 1.)?),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let tightened = Range {
+            kind: Kind::Number,
+            lower,
+            upper,
+            excluded: range.excluded.clone(),
+        }
+        .normalize()?;
+
+        let stable = cmp_lower(&tightened.lower, &range.lower).is_eq()
+            && cmp_upper(&tightened.upper, &range.upper).is_eq();
+        range = tightened;
+        if stable {
+            return Some(range);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Number(number) => number.as_f64().unwrap_or(f64::NAN),
+        Value::Bool(_) | Value::String(_) => f64::NAN,
+    }
+}
+
+#[cfg(feature = "std")]
+fn is_integer(value: &Value) -> bool {
+    is_integral(as_f64(value))
+}
+
+/// Rounds towards negative infinity, like `f64::floor` that is not available without `std`.
+fn floor(value: f64) -> f64 {
+    // Values from 2^52 on have no fractional part, and NaN and infinities are left untouched.
+    const MIN_INTEGRAL: f64 = 4_503_599_627_370_496.;
+
+    if value.is_nan() || value.abs() >= MIN_INTEGRAL {
+        return value;
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    let truncated = value as i64 as f64;
+    if truncated > value {
+        truncated - 1.
+    } else {
+        truncated
+    }
+}
+
+#[cfg(feature = "std")]
+/// Rounds towards positive infinity, like `f64::ceil` that is not available without `std`.
+fn ceil(value: f64) -> f64 {
+    -floor(-value)
+}
+
+fn is_integral(value: f64) -> bool {
+    value - floor(value) == 0.
+}
+
+#[cfg(feature = "std")]
+/// Creates a numeric value from a `f64`, using an integer representation when possible.
+///
+/// Returns `None` for infinite and NaN values.
+pub(crate) fn value_from_f64(value: f64) -> Option<Value> {
+    serde_json::Number::from_f64(value).map(|number| normalize_value(&Value::Number(number)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{condition::Condition, hazard::JsonPointer};
+
+    use super::*;
+
+    fn cond<T>(pointer: &str, op: Option<Operation>, value: T) -> hazard::Condition
+    where
+        T: TryInto<Value>,
+        T::Error: core::fmt::Debug,
+    {
+        let value = value.try_into().unwrap();
+        hazard::Condition {
+            pointer: JsonPointer::try_from(pointer).unwrap(),
+            condition: match op {
+                Some(op) => Condition::Expr(condition::Expr { value, op }),
+                None => Condition::Value(value),
+            },
+        }
+    }
+
+    #[test]
+    fn empty_ranges() {
+        assert!(Branch::from_conditions(&[
+            cond("/a", Some(Operation::Lt), 3),
+            cond("/a", Some(Operation::Gt), 5),
+        ])
+        .is_none());
+
+        assert!(Branch::from_conditions(&[
+            cond("/a", Some(Operation::Le), 3),
+            cond("/a", Some(Operation::Ge), 3),
+            cond("/a", Some(Operation::Ne), 3),
+        ])
+        .is_none());
+
+        assert!(Branch::from_conditions(&[cond("/a", Some(Operation::Lt), false)]).is_none());
+        assert!(Branch::from_conditions(&[cond("/a", None, 3), cond("/a", None, "3")]).is_none());
+        assert!(Branch::from_conditions(&[
+            cond("/a", Some(Operation::Ne), true),
+            cond("/a", Some(Operation::Ne), false),
+        ])
+        .is_none());
+    }
+
+    #[test]
+    fn collapse_to_point() {
+        let branch = Branch::from_conditions(&[
+            cond("/a", Some(Operation::Le), 3),
+            cond("/a", Some(Operation::Ge), 3),
+            cond("/b", Some(Operation::Gt), false),
+        ])
+        .unwrap();
+
+        assert!(branch.0["/a"].is_subset(&Range::point(3.into())));
+        assert!(branch.0["/b"].is_subset(&Range::point(true.into())));
+    }
+
+    #[test]
+    fn numbers_with_different_representations() {
+        let branch = Branch::from_conditions(&[
+            cond("/a", Some(Operation::Le), Value::try_from(3.).unwrap()),
+            cond("/a", Some(Operation::Ge), 3),
+        ])
+        .unwrap();
+
+        assert!(branch.0["/a"].contains(&3.into()));
+    }
+
+    #[test]
+    fn branch_implication() {
+        let strong =
+            Branch::from_conditions(&[cond("/a", Some(Operation::Ge), 5), cond("/b", None, true)])
+                .unwrap();
+        let weak = Branch::from_conditions(&[cond("/a", Some(Operation::Gt), 3)]).unwrap();
+
+        assert!(strong.implies(&weak));
+        assert!(weak.implies(&strong).not());
+
+        let excluded = Branch::from_conditions(&[cond("/a", Some(Operation::Ne), 4)]).unwrap();
+        assert!(strong.implies(&excluded));
+        assert!(weak.implies(&excluded).not());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn integer_domain() {
+        let range = |conditions: &[hazard::Condition]| {
+            Branch::from_conditions(conditions).unwrap().0["/a"].clone()
+        };
+        let domain = Domain::new(
+            range(&[
+                cond("/a", Some(Operation::Ge), 0),
+                cond("/a", Some(Operation::Le), 10),
+            ]),
+            true,
+            None,
+        )
+        .unwrap();
+
+        assert!(domain
+            .restrict(&range(&[
+                cond("/a", Some(Operation::Gt), 3),
+                cond("/a", Some(Operation::Lt), 4),
+            ]))
+            .is_none());
+        assert!(domain
+            .restrict(&range(&[
+                cond("/a", Some(Operation::Ge), 3),
+                cond("/a", Some(Operation::Le), 4),
+                cond("/a", Some(Operation::Ne), 3),
+                cond("/a", Some(Operation::Ne), 4),
+            ]))
+            .is_none());
+
+        let restricted = domain
+            .restrict(&range(&[cond("/a", Some(Operation::Gt), 4.5)]))
+            .unwrap();
+        assert!(restricted.is_subset(&range(&[
+            cond("/a", Some(Operation::Ge), 5),
+            cond("/a", Some(Operation::Le), 10),
+        ])));
+
+        assert!(domain.is_covered_by(&range(&[cond("/a", Some(Operation::Gt), -1)])));
+        assert!(domain
+            .is_covered_by(&range(&[cond("/a", Some(Operation::Gt), 0)]))
+            .not());
+    }
+
+    #[test]
+    fn dnf_implication() {
+        let a = [
+            vec![cond("/a", Some(Operation::Ge), 5)],
+            vec![cond("/b", None, "on")],
+        ];
+        let b = [
+            vec![cond("/b", Some(Operation::Ne), "off")],
+            vec![cond("/a", Some(Operation::Ge), 2)],
+        ];
+
+        assert!(implies(&a, &b));
+        assert!(implies(&b, &a).not());
+        assert!(implies(&[], &a));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn rounding() {
+        for value in [
+            0.,
+            -0.,
+            0.5,
+            -0.5,
+            1.,
+            -1.,
+            2.5,
+            -2.5,
+            1e15 + 0.5,
+            1e300,
+            f64::INFINITY,
+        ] {
+            assert_eq!(floor(value), value.floor(), "{value}");
+            #[cfg(feature = "std")]
+            assert_eq!(ceil(value), value.ceil(), "{value}");
+            assert_eq!(is_integral(value), value.fract() == 0., "{value}");
+        }
+        assert!(floor(f64::NAN).is_nan());
+        #[cfg(feature = "std")]
+        assert!(ceil(f64::NAN).is_nan());
+        assert!(is_integral(f64::NAN).not());
+    }
+}
@@ -1,13 +1,22 @@
 //! Hazard-related structures.
 
-use std::{borrow::Cow, ops::Deref};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Deref, Not},
+    str::FromStr,
+};
 
+use jsonptr::Resolve;
 use serde::{Deserialize, Serialize};
 
-use crate::{condition, risk};
+use crate::{condition, domain, risk};
 
 /// An hazard element.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Hazard {
     /// The associated risk.
     #[serde(rename = "sho:risk")]
@@ -35,6 +44,118 @@ pub struct Hazard {
     pub conditions: Vec<Vec<Condition>>,
 }
 
+impl Hazard {
+    /// Rewrites the [`conditions`] in a canonical form.
+    ///
+    /// Two hazards expressing the same conditions in different ways, like with a different order
+    /// of the clauses or with redundant clauses, usually have the same canonical form. In order to
+    /// obtain it:
+    ///
+    /// - the clauses referring to the same JSON pointer inside an `AND` set are merged, i.e.
+    ///   `x >= 3 && x >= 5` becomes `x >= 5` and `x >= 3 && x <= 3` becomes `x == 3`;
+    /// - the `AND` sets that can never be satisfied are removed;
+    /// - the `AND` sets that are more restrictive than another one are removed, i.e.
+    ///   `(x >= 5 && y == true) || x >= 3` becomes `x >= 3`;
+    /// - numbers without a fractional part are expressed as integers;
+    /// - the clauses and the `AND` sets are sorted.
+    ///
+    /// The canonical form always triggers the hazard in the same states of the original one.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")]
+    /// # fn main() {
+    /// use sifis_td::Sifis;
+    ///
+    /// let sifis_a = Sifis::builder()
+    ///     .fire_hazard(3, |cond| {
+    ///         cond.when("/properties/level")
+    ///             .ge(3)
+    ///             .and("/properties/on")
+    ///             .eq(true)
+    ///             .and("/properties/level")
+    ///             .ge(5)
+    ///             .or(|cond| cond.when("/properties/level").ge(8))
+    ///             .or(|cond| {
+    ///                 cond.when("/properties/level")
+    ///                     .ge(9)
+    ///                     .and("/properties/on")
+    ///                     .eq(true)
+    ///             })
+    ///     })
+    ///     .build();
+    /// let sifis_b = Sifis::builder()
+    ///     .fire_hazard(3, |cond| {
+    ///         cond.when("/properties/level")
+    ///             .ge(8.)
+    ///             .or(|cond| {
+    ///                 cond.when("/properties/on")
+    ///                     .eq(true)
+    ///                     .and("/properties/level")
+    ///                     .ge(5)
+    ///             })
+    ///     })
+    ///     .build();
+    ///
+    /// let mut hazard_a = sifis_a.hazards[0].clone();
+    /// let mut hazard_b = sifis_b.hazards[0].clone();
+    /// assert_ne!(hazard_a, hazard_b);
+    ///
+    /// hazard_a.canonicalize();
+    /// hazard_b.canonicalize();
+    /// assert_eq!(hazard_a, hazard_b);
+    /// # }
+    /// # #[cfg(not(feature = "std"))]
+    /// # fn main() {}
+    /// ```
+    ///
+    /// [`conditions`]: Hazard::conditions
+    pub fn canonicalize(&mut self) {
+        self.conditions = domain::canonicalize(&self.conditions);
+    }
+
+    /// Returns a copy of the hazard with the [`conditions`] in canonical form.
+    ///
+    /// See [`canonicalize`] for more information.
+    ///
+    /// [`conditions`]: Hazard::conditions
+    /// [`canonicalize`]: Hazard::canonicalize
+    #[must_use]
+    pub fn canonical(&self) -> Self {
+        Self {
+            risk: self.risk,
+            conditions: domain::canonicalize(&self.conditions),
+        }
+    }
+
+    /// Checks whether the hazard is triggered by the state of a Thing.
+    ///
+    /// See [`Condition::is_satisfied_by`] for more information about the state. A hazard without
+    /// any set of conditions is never triggered.
+    #[must_use]
+    pub fn is_triggered_by(&self, state: &serde_json::Value) -> bool {
+        self.conditions
+            .iter()
+            .any(|conditions| conditions.iter().all(|cond| cond.is_satisfied_by(state)))
+    }
+
+    /// Checks whether two hazards have the same risk and are triggered by the same states.
+    ///
+    /// Unlike the `PartialEq` implementation, this ignores the differences in how the
+    /// [`conditions`] are expressed.
+    ///
+    /// The check is conservative: when the conditions of a hazard are equivalent to the ones of
+    /// the other only considering multiple `AND` sets together (like `x < 5 || x >= 5` compared to
+    /// `x != "a"`), `false` is returned.
+    ///
+    /// [`conditions`]: Hazard::conditions
+    #[must_use]
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.risk == other.risk
+            && domain::implies(&self.conditions, &other.conditions)
+            && domain::implies(&other.conditions, &self.conditions)
+    }
+}
+
 /// The risk associated with the hazard, with the level of risk.
 ///
 /// This structure only contains the `id` of the risk and not all its details. These are available
@@ -42,6 +163,7 @@ pub struct Hazard {
 ///
 /// [`risk::Detail`]: crate::risk::Detail
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Risk {
     /// The id of the associated risk.
     #[serde(rename = "@id")]
@@ -54,6 +176,7 @@ pub struct Risk {
 
 /// A condition that needs to be satisfied based on the data available from the Thing.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Condition {
     /// The JSON pointer for the expression to be evaluated.
     ///
@@ -66,14 +189,120 @@ pub struct Condition {
     pub condition: condition::Condition,
 }
 
+impl Condition {
+    /// Checks whether the state of a Thing satisfies the condition.
+    ///
+    /// The state is a JSON document with the same structure of the Thing Description, in which
+    /// the affordances contain their current values (i.e.: `{"properties": {"on": true}}`). When
+    /// the [`pointer`] cannot be resolved, the condition is not satisfied.
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use sifis_td::{condition, hazard::Condition};
+    ///
+    /// let condition = Condition {
+    ///     pointer: "/properties/on".try_into().unwrap(),
+    ///     condition: condition::Condition::Value(true.into()),
+    /// };
+    /// assert!(condition.is_satisfied_by(&json!({ "properties": { "on": true } })));
+    /// assert!(!condition.is_satisfied_by(&json!({ "properties": { "on": false } })));
+    /// assert!(!condition.is_satisfied_by(&json!({ "properties": {} })));
+    /// ```
+    ///
+    /// [`pointer`]: Condition::pointer
+    #[must_use]
+    pub fn is_satisfied_by(&self, state: &serde_json::Value) -> bool {
+        state
+            .resolve(&self.pointer.0)
+            .is_ok_and(|value| self.condition.is_satisfied_by(value))
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.condition {
+            condition::Condition::Value(value) => write!(f, "{} == {value}", &*self.pointer),
+            condition::Condition::Expr(condition::Expr { value, op }) => {
+                write!(f, "{} {op} {value}", &*self.pointer)
+            }
+        }
+    }
+}
+
 /// An opaque abstraction for a JSON Pointer (RFC 6901).
+///
+/// Pointers are ordered by their string representation.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct JsonPointer(pub(crate) jsonptr::Pointer);
 
+impl PartialOrd for JsonPointer {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonPointer {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.as_str().cmp(other.0.as_str())
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for JsonPointer {
+    fn schema_name() -> Cow<'static, str> {
+        "JsonPointer".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A JSON Pointer (RFC 6901).",
+            "type": "string",
+            "pattern": "^(/([^~/]|~[01])*)*$",
+        })
+    }
+}
+
 /// An error caused by an invalid JSON pointer.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InvalidJsonPointer<'a>(Cow<'a, str>);
 
+impl JsonPointer {
+    /// Moves the pointer from `base` to `new_base`.
+    ///
+    /// If the pointer is equal to `base` or it is one of its descendants, the common prefix is
+    /// replaced with `new_base` and the new pointer is returned. Otherwise, `None` is returned.
+    ///
+    /// ```
+    /// use sifis_td::hazard::JsonPointer;
+    ///
+    /// let pointer = JsonPointer::try_from("/properties/on").unwrap();
+    /// let base = "/properties".try_into().unwrap();
+    /// let new_base = "/properties/camera".try_into().unwrap();
+    ///
+    /// assert_eq!(
+    ///     pointer.rebase(&base, &new_base).as_deref(),
+    ///     Some("/properties/camera/on"),
+    /// );
+    /// assert_eq!(pointer.rebase(&new_base, &base), None);
+    /// ```
+    #[must_use]
+    pub fn rebase(&self, base: &JsonPointer, new_base: &JsonPointer) -> Option<JsonPointer> {
+        let rest = self.0.as_str().strip_prefix(base.0.as_str())?;
+        if rest.is_empty().not() && rest.starts_with('/').not() {
+            return None;
+        }
+
+        let mut pointer = String::with_capacity(new_base.len() + rest.len());
+        pointer.push_str(new_base);
+        pointer.push_str(rest);
+
+        // The concatenation of two valid JSON pointers is always a valid JSON pointer.
+        jsonptr::Pointer::try_from(&*pointer).ok().map(Self)
+    }
+}
+
 impl<'a> TryFrom<&'a str> for JsonPointer {
     type Error = InvalidJsonPointer<'a>;
 
@@ -112,108 +341,225 @@ impl AsRef<str> for JsonPointer {
     }
 }
 
+impl Borrow<str> for JsonPointer {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
 /// The ID of an hazard.
+///
+/// Each id has a stable numeric [`code`], which can be used for compact encodings through the
+/// [`id_code`] module.
+///
+/// [`code`]: Id::code
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
+#[repr(u8)]
 pub enum Id {
     /// Air poisoning
     #[serde(rename = "sho:AirPoisoning")]
-    AirPoisoning,
+    AirPoisoning = 0,
 
     /// Asphyxia
     #[serde(rename = "sho:Asphyxia")]
-    Asphyxia,
+    Asphyxia = 1,
 
     /// Audio video record and store
     #[serde(rename = "sho:AudioVideoRecordAndStore")]
-    AudioVideoRecordAndStore,
+    AudioVideoRecordAndStore = 2,
 
     /// Audio video stream
     #[serde(rename = "sho:AudioVideoStream")]
-    AudioVideoStream,
+    AudioVideoStream = 3,
 
     /// Burn
     #[serde(rename = "sho:Burn")]
-    Burn,
+    Burn = 4,
 
     /// Electric energy consumption
     #[serde(rename = "sho:ElectricEnergyConsumption")]
-    ElectricEnergyConsumption,
+    ElectricEnergyConsumption = 5,
 
     /// Explosion
     #[serde(rename = "sho:Explosion")]
-    Explosion,
+    Explosion = 6,
 
     /// Fire hazard
     #[serde(rename = "sho:FireHazard")]
-    FireHazard,
+    FireHazard = 7,
 
     /// Gas consumption
     #[serde(rename = "sho:GasConsumption")]
-    GasConsumption,
+    GasConsumption = 8,
 
     /// Log energy consumption
     #[serde(rename = "sho:LogEnergyConsumption")]
-    LogEnergyConsumption,
+    LogEnergyConsumption = 9,
 
     /// Log usage time
     #[serde(rename = "sho:LogUsageTime")]
-    LogUsageTime,
+    LogUsageTime = 10,
 
     /// Pay subscription fee
     #[serde(rename = "sho:PaySubscriptionFee")]
-    PaySubscriptionFee,
+    PaySubscriptionFee = 11,
 
     /// Power outage
     #[serde(rename = "sho:PowerOutage")]
-    PowerOutage,
+    PowerOutage = 12,
 
     /// Power surge
     #[serde(rename = "sho:PowerSurge")]
-    PowerSurge,
+    PowerSurge = 13,
 
     /// Record issued commands
     #[serde(rename = "sho:RecordIssuedCommands")]
-    RecordIssuedCommands,
+    RecordIssuedCommands = 14,
 
     /// Record user preferences
     #[serde(rename = "sho:RecordUserPreferences")]
-    RecordUserPreferences,
+    RecordUserPreferences = 15,
 
     /// Scald
     #[serde(rename = "sho:Scald")]
-    Scald,
+    Scald = 16,
 
     /// Spend money
     #[serde(rename = "sho:SpendMoney")]
-    SpendMoney,
+    SpendMoney = 17,
 
     /// Spoiled food
     #[serde(rename = "sho:SpoiledFood")]
-    SpoiledFood,
+    SpoiledFood = 18,
 
     /// Take device screenshots
     #[serde(rename = "sho:TakeDeviceScreenshots")]
-    TakeDeviceScreenshots,
+    TakeDeviceScreenshots = 19,
 
     /// Take pictures
     #[serde(rename = "sho:TakePictures")]
-    TakePictures,
+    TakePictures = 20,
 
     /// Unauthorised physical access
     #[serde(rename = "sho:UnauthorisedPhysicalAccess")]
-    UnauthorisedPhysicalAccess,
+    UnauthorisedPhysicalAccess = 21,
 
     /// Water consumption
     #[serde(rename = "sho:WaterConsumption")]
-    WaterConsumption,
+    WaterConsumption = 22,
 
     /// Water flooding
     #[serde(rename = "sho:WaterFlooding")]
-    WaterFlooding,
+    WaterFlooding = 23,
 }
 
 impl Id {
+    /// The stable numeric code of the hazard.
+    ///
+    /// Codes are never reused, new hazards always get new codes.
+    ///
+    /// ```
+    /// use sifis_td::hazard::Id;
+    ///
+    /// assert_eq!(Id::FireHazard.code(), 7);
+    /// assert_eq!(Id::from_code(7), Some(Id::FireHazard));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the hazard with the given numeric [`code`], if any.
+    ///
+    /// [`code`]: Id::code
+    #[must_use]
+    pub const fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            0 => Self::AirPoisoning,
+            1 => Self::Asphyxia,
+            2 => Self::AudioVideoRecordAndStore,
+            3 => Self::AudioVideoStream,
+            4 => Self::Burn,
+            5 => Self::ElectricEnergyConsumption,
+            6 => Self::Explosion,
+            7 => Self::FireHazard,
+            8 => Self::GasConsumption,
+            9 => Self::LogEnergyConsumption,
+            10 => Self::LogUsageTime,
+            11 => Self::PaySubscriptionFee,
+            12 => Self::PowerOutage,
+            13 => Self::PowerSurge,
+            14 => Self::RecordIssuedCommands,
+            15 => Self::RecordUserPreferences,
+            16 => Self::Scald,
+            17 => Self::SpendMoney,
+            18 => Self::SpoiledFood,
+            19 => Self::TakeDeviceScreenshots,
+            20 => Self::TakePictures,
+            21 => Self::UnauthorisedPhysicalAccess,
+            22 => Self::WaterConsumption,
+            23 => Self::WaterFlooding,
+            _ => return None,
+        })
+    }
+
+    /// The name of the hazard in Thing Descriptions, i.e. `sho:FireHazard`.
+    ///
+    /// ```
+    /// use sifis_td::hazard::Id;
+    ///
+    /// assert_eq!(Id::FireHazard.as_str(), "sho:FireHazard");
+    /// ```
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AirPoisoning => "sho:AirPoisoning",
+            Self::Asphyxia => "sho:Asphyxia",
+            Self::AudioVideoRecordAndStore => "sho:AudioVideoRecordAndStore",
+            Self::AudioVideoStream => "sho:AudioVideoStream",
+            Self::Burn => "sho:Burn",
+            Self::ElectricEnergyConsumption => "sho:ElectricEnergyConsumption",
+            Self::Explosion => "sho:Explosion",
+            Self::FireHazard => "sho:FireHazard",
+            Self::GasConsumption => "sho:GasConsumption",
+            Self::LogEnergyConsumption => "sho:LogEnergyConsumption",
+            Self::LogUsageTime => "sho:LogUsageTime",
+            Self::PaySubscriptionFee => "sho:PaySubscriptionFee",
+            Self::PowerOutage => "sho:PowerOutage",
+            Self::PowerSurge => "sho:PowerSurge",
+            Self::RecordIssuedCommands => "sho:RecordIssuedCommands",
+            Self::RecordUserPreferences => "sho:RecordUserPreferences",
+            Self::Scald => "sho:Scald",
+            Self::SpendMoney => "sho:SpendMoney",
+            Self::SpoiledFood => "sho:SpoiledFood",
+            Self::TakeDeviceScreenshots => "sho:TakeDeviceScreenshots",
+            Self::TakePictures => "sho:TakePictures",
+            Self::UnauthorisedPhysicalAccess => "sho:UnauthorisedPhysicalAccess",
+            Self::WaterConsumption => "sho:WaterConsumption",
+            Self::WaterFlooding => "sho:WaterFlooding",
+        }
+    }
+
+    /// The category of the hazard.
+    #[inline]
+    #[must_use]
+    pub fn category(self) -> Category {
+        self.risk().category()
+    }
+
+    /// The details of the hazard from the catalog in [`risk`].
+    ///
+    /// [`risk`]: crate::risk
+    #[inline]
+    #[must_use]
+    pub const fn detail(self) -> risk::Detail {
+        self.risk()
+    }
+
     pub(crate) const fn risk(self) -> risk::Detail {
         match self {
             Id::AirPoisoning => risk::AIR_POISONING,
@@ -244,8 +590,105 @@ impl Id {
     }
 }
 
+impl FromStr for Id {
+    type Err = UnknownId;
+
+    /// Parses the name of a hazard in Thing Descriptions, the inverse of [`as_str`].
+    ///
+    /// ```
+    /// use sifis_td::hazard::Id;
+    ///
+    /// assert_eq!("sho:FireHazard".parse(), Ok(Id::FireHazard));
+    /// assert!("FireHazard".parse::<Id>().is_err());
+    /// ```
+    ///
+    /// [`as_str`]: Id::as_str
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        risk::ALL
+            .iter()
+            .map(risk::Detail::id)
+            .find(|id| id.as_str() == s)
+            .ok_or_else(|| UnknownId(s.into()))
+    }
+}
+
+/// An error for names that do not identify any hazard.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownId(String);
+
+impl Display for UnknownId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown hazard id {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownId {}
+
+/// A compact encoding of [`Id`] as its numeric [`code`], to be used with `#[serde(with)]`.
+///
+/// The default encoding uses the `sho:` names, which are needed in Thing Descriptions. When the
+/// data are exchanged between constrained devices, i.e. using CBOR over `CoAP`, the codes can be
+/// used instead:
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use sifis_td::hazard;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Alarm {
+///     #[serde(with = "hazard::id_code")]
+///     id: hazard::Id,
+///     level: u8,
+/// }
+///
+/// let alarm = Alarm {
+///     id: hazard::Id::FireHazard,
+///     level: 7,
+/// };
+/// let json = serde_json::to_string(&alarm).unwrap();
+/// assert_eq!(json, r#"{"id":7,"level":7}"#);
+/// assert_eq!(serde_json::from_str::<Alarm>(&json).unwrap(), alarm);
+/// ```
+///
+/// [`code`]: Id::code
+pub mod id_code {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use super::Id;
+
+    /// Serializes the id as its numeric code.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the serializer.
+    #[inline]
+    pub fn serialize<S>(id: &Id, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(id.code())
+    }
+
+    /// Deserializes an id from its numeric code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not the code of a known hazard.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Id, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = u8::deserialize(deserializer)?;
+        Id::from_code(code).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(code.into()), &"a hazard code")
+        })
+    }
+}
+
 /// The category of an hazard.
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 pub enum Category {
     /// Financial
@@ -261,8 +704,32 @@ pub enum Category {
     Safety,
 }
 
+impl Category {
+    /// The name of the category in Thing Descriptions, i.e. `sho:Safety`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Financial => "sho:Financial",
+            Self::Privacy => "sho:Privacy",
+            Self::Safety => "sho:Safety",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Financial => "Financial",
+            Self::Privacy => "Privacy",
+            Self::Safety => "Safety",
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::{string::ToString, vec};
+
     use serde_json::json;
 
     use crate::hazard;
@@ -290,6 +757,193 @@ mod tests {
         );
     }
 
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn canonicalize() {
+        let mut hazard = Hazard {
+            risk: Risk {
+                id: hazard::Id::FireHazard,
+                level: 3,
+            },
+            conditions: vec![
+                vec![
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: 10.into(),
+                            op: condition::Operation::Lt,
+                        }),
+                    },
+                    Condition {
+                        pointer: "/properties/a".try_into().unwrap(),
+                        condition: condition::Condition::Value(true.into()),
+                    },
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: condition::Value::try_from(3.0).unwrap(),
+                            op: condition::Operation::Ge,
+                        }),
+                    },
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: 5.into(),
+                            op: condition::Operation::Ge,
+                        }),
+                    },
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: 12.into(),
+                            op: condition::Operation::Ne,
+                        }),
+                    },
+                ],
+                vec![Condition {
+                    pointer: "/properties/c".try_into().unwrap(),
+                    condition: condition::Condition::Expr(condition::Expr {
+                        value: "off".into(),
+                        op: condition::Operation::Ne,
+                    }),
+                }],
+                vec![Condition {
+                    pointer: "/properties/a".try_into().unwrap(),
+                    condition: condition::Condition::Expr(condition::Expr {
+                        value: 10.into(),
+                        op: condition::Operation::Gt,
+                    }),
+                }],
+                vec![
+                    Condition {
+                        pointer: "/properties/c".try_into().unwrap(),
+                        condition: condition::Condition::Value("on".into()),
+                    },
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Value(2.into()),
+                    },
+                ],
+                vec![
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: 2.into(),
+                            op: condition::Operation::Lt,
+                        }),
+                    },
+                    Condition {
+                        pointer: "/properties/b".try_into().unwrap(),
+                        condition: condition::Condition::Expr(condition::Expr {
+                            value: 4.into(),
+                            op: condition::Operation::Gt,
+                        }),
+                    },
+                ],
+            ],
+        };
+        hazard.canonicalize();
+
+        assert_eq!(
+            serde_json::to_value(&hazard).unwrap(),
+            json!({
+                "sho:risk": {
+                    "@id": "sho:FireHazard",
+                    "sho:level": 3,
+                },
+                "sho:conditions": [
+                    [
+                        {
+                            "sho:pointer": "/properties/a",
+                            "sho:condition": true,
+                        },
+                        {
+                            "sho:pointer": "/properties/b",
+                            "sho:condition": {
+                                "sho:value": 5,
+                                "sho:op": "ge",
+                            },
+                        },
+                        {
+                            "sho:pointer": "/properties/b",
+                            "sho:condition": {
+                                "sho:value": 10,
+                                "sho:op": "lt",
+                            },
+                        },
+                    ],
+                    [{
+                        "sho:pointer": "/properties/a",
+                        "sho:condition": {
+                            "sho:value": 10,
+                            "sho:op": "gt",
+                        },
+                    }],
+                    [{
+                        "sho:pointer": "/properties/c",
+                        "sho:condition": {
+                            "sho:value": "off",
+                            "sho:op": "ne",
+                        },
+                    }],
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn semantically_eq() {
+        let hazard = |conditions| Hazard {
+            risk: Risk {
+                id: hazard::Id::FireHazard,
+                level: 3,
+            },
+            conditions,
+        };
+        let cond = |pointer: &str, value: i32, op| Condition {
+            pointer: pointer.try_into().unwrap(),
+            condition: condition::Condition::Expr(condition::Expr {
+                value: value.into(),
+                op,
+            }),
+        };
+
+        let a = hazard(vec![
+            vec![
+                cond("/properties/a", 3, condition::Operation::Ge),
+                cond("/properties/b", 4, condition::Operation::Lt),
+            ],
+            vec![cond("/properties/c", 1, condition::Operation::Ne)],
+        ]);
+        let b = hazard(vec![
+            vec![cond("/properties/c", 1, condition::Operation::Ne)],
+            vec![
+                cond("/properties/b", 4, condition::Operation::Lt),
+                cond("/properties/a", 2, condition::Operation::Gt),
+                cond("/properties/a", 3, condition::Operation::Ge),
+            ],
+        ]);
+        let c = hazard(vec![vec![cond(
+            "/properties/c",
+            1,
+            condition::Operation::Ne,
+        )]]);
+
+        assert_ne!(a, b);
+        assert!(a.semantically_eq(&b));
+        assert_eq!(a.canonical(), b.canonical());
+        assert!(a.semantically_eq(&c).not());
+        assert!(a
+            .semantically_eq(&Hazard {
+                risk: Risk {
+                    id: hazard::Id::Explosion,
+                    level: 3,
+                },
+                ..b
+            })
+            .not());
+    }
+
     #[test]
     fn deserialize_hazard_without_conditions() {
         let hazard = json!({
@@ -310,4 +964,65 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn id_codes() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Compact(#[serde(with = "id_code")] Id);
+
+        let mut codes: Vec<_> = risk::ALL.iter().map(|detail| detail.id().code()).collect();
+        for detail in risk::ALL {
+            assert_eq!(Id::from_code(detail.id().code()), Some(detail.id()));
+        }
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), risk::ALL.len());
+        assert_eq!(Id::from_code(24), None);
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&Compact(Id::WaterFlooding), &mut cbor).unwrap();
+        assert_eq!(cbor, [0x17]);
+        assert_eq!(
+            ciborium::from_reader::<Compact, _>(&*cbor).unwrap(),
+            Compact(Id::WaterFlooding),
+        );
+
+        let err = serde_json::from_str::<Compact>("24").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value: integer `24`, expected a hazard code",
+        );
+    }
+
+    #[test]
+    fn names() {
+        use crate::condition::Operation;
+
+        for detail in risk::ALL {
+            assert_eq!(
+                serde_json::to_value(detail.id()).unwrap(),
+                detail.id().as_str(),
+            );
+            assert_eq!(detail.id().as_str().parse(), Ok(detail.id()));
+            assert_eq!(
+                serde_json::to_value(detail.category()).unwrap(),
+                detail.category().as_str(),
+            );
+        }
+
+        for op in [
+            Operation::Lt,
+            Operation::Le,
+            Operation::Ne,
+            Operation::Gt,
+            Operation::Ge,
+        ] {
+            assert_eq!(serde_json::to_value(op).unwrap(), op.as_str());
+        }
+
+        assert_eq!(
+            "sho:Unknown".parse::<Id>().unwrap_err().to_string(),
+            "unknown hazard id sho:Unknown",
+        );
+    }
 }
@@ -0,0 +1,338 @@
+//! Aggregation of the risks of all the Things of a home.
+//!
+//! A [`Home`] holds the [`Sifis`] extension and the latest state of each Thing, keeping their
+//! risks up to date, and it answers queries about the whole set of Things.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{hazard, home::Home, Sifis};
+//!
+//! let lamp = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .build();
+//! let camera = Sifis::builder()
+//!     .take_pictures(5, |cond| cond.when("/properties/on").eq(true))
+//!     .fire_hazard(1, |cond| cond.when("/properties/on").eq(true))
+//!     .build();
+//!
+//! let mut home = Home::new();
+//! home.insert("lamp", lamp, json!({ "properties": { "brightness": 50 } }));
+//! home.insert("camera", camera, json!({ "properties": { "on": true } }));
+//!
+//! assert_eq!(
+//!     home.max_risks(),
+//!     [
+//!         hazard::Risk {
+//!             id: hazard::Id::TakePictures,
+//!             level: 5,
+//!         },
+//!         hazard::Risk {
+//!             id: hazard::Id::FireHazard,
+//!             level: 3,
+//!         },
+//!     ],
+//! );
+//! assert_eq!(home.exposing(hazard::Category::Privacy), [&"camera"]);
+//! assert_eq!(home.riskiest(1), [(&"camera", 5)]);
+//! ```
+
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{btree_map, BTreeMap},
+};
+
+use serde_json::Value;
+
+use crate::{compiled::CompiledSifis, hazard, insert_max_risk, Sifis};
+
+/// The Things of a home, with their latest states and risks.
+///
+/// The Things are identified by keys of type `K` and they are sorted by key.
+#[derive(Debug, Clone)]
+pub struct Home<K> {
+    things: BTreeMap<K, Entry>,
+}
+
+/// A Thing inside a [`Home`].
+#[derive(Debug, Clone)]
+pub struct Entry {
+    sifis: Sifis,
+    compiled: CompiledSifis,
+    state: Value,
+    risks: Vec<hazard::Risk>,
+}
+
+impl<K> Default for Home<K> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            things: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord> Home<K> {
+    /// Creates an empty home.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a Thing with its current state, returning the previous entry with the same key.
+    pub fn insert(&mut self, thing: K, sifis: Sifis, state: Value) -> Option<Entry> {
+        self.things.insert(thing, Entry::new(sifis, state))
+    }
+
+    /// Removes a Thing, returning its entry.
+    pub fn remove<Q>(&mut self, thing: &Q) -> Option<Entry>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.things.remove(thing)
+    }
+
+    /// Replaces the state of a Thing, returning its new risks.
+    ///
+    /// Returns `None` if the Thing is not in the home.
+    pub fn update_state<Q>(&mut self, thing: &Q, state: Value) -> Option<&[hazard::Risk]>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let entry = self.things.get_mut(thing)?;
+        entry.risks = entry.compiled.evaluate(&state);
+        entry.state = state;
+        Some(&entry.risks)
+    }
+
+    /// Returns the entry of a Thing.
+    #[inline]
+    #[must_use]
+    pub fn get<Q>(&self, thing: &Q) -> Option<&Entry>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.things.get(thing)
+    }
+
+    /// Returns an iterator over the Things, sorted by key.
+    #[inline]
+    pub fn iter(&self) -> btree_map::Iter<'_, K, Entry> {
+        self.things.iter()
+    }
+
+    /// Returns the number of Things.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.things.len()
+    }
+
+    /// Returns `true` if there are no Things.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.things.is_empty()
+    }
+
+    /// Returns the current maximum level of each risk across the home.
+    ///
+    /// The risks are sorted by decreasing level, then by the first Thing exposing them.
+    #[must_use]
+    pub fn max_risks(&self) -> Vec<hazard::Risk> {
+        let mut risks = self.things.values().flat_map(|entry| &entry.risks).fold(
+            Vec::new(),
+            |mut risks, &risk| {
+                insert_max_risk(&mut risks, risk);
+                risks
+            },
+        );
+
+        risks.sort_by_key(|risk| Reverse(risk.level));
+        risks
+    }
+
+    /// Returns the Things currently exposing at least one risk of the given category.
+    #[must_use]
+    pub fn exposing(&self, category: hazard::Category) -> Vec<&K> {
+        self.things
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .risks
+                    .iter()
+                    .any(|risk| risk.id.category() == category)
+            })
+            .map(|(thing, _)| thing)
+            .collect()
+    }
+
+    /// Returns at most `n` Things with the highest current risk levels, with their maximum level.
+    ///
+    /// The Things without risks are never returned. Things with the same maximum level are sorted
+    /// by the number of their risks, in decreasing order, then by key.
+    #[must_use]
+    pub fn riskiest(&self, n: usize) -> Vec<(&K, u8)> {
+        let mut things: Vec<_> = self
+            .things
+            .iter()
+            .filter_map(|(thing, entry)| {
+                let level = entry.max_level()?;
+                Some((thing, level, entry.risks.len()))
+            })
+            .collect();
+
+        // The sort is stable, therefore the order by key is kept for ties.
+        things.sort_by_key(|&(_, level, count)| Reverse((level, count)));
+        things
+            .into_iter()
+            .take(n)
+            .map(|(thing, level, _)| (thing, level))
+            .collect()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a Home<K> {
+    type Item = (&'a K, &'a Entry);
+    type IntoIter = btree_map::Iter<'a, K, Entry>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.things.iter()
+    }
+}
+
+impl<K: Ord> FromIterator<(K, Sifis, Value)> for Home<K> {
+    fn from_iter<T: IntoIterator<Item = (K, Sifis, Value)>>(iter: T) -> Self {
+        let things = iter
+            .into_iter()
+            .map(|(thing, sifis, state)| (thing, Entry::new(sifis, state)))
+            .collect();
+
+        Self { things }
+    }
+}
+
+impl Entry {
+    fn new(sifis: Sifis, state: Value) -> Self {
+        let compiled = sifis.compile();
+        let risks = compiled.evaluate(&state);
+
+        Self {
+            sifis,
+            compiled,
+            state,
+            risks,
+        }
+    }
+
+    /// The `Sifis` extension of the Thing.
+    #[inline]
+    #[must_use]
+    pub fn sifis(&self) -> &Sifis {
+        &self.sifis
+    }
+
+    /// The latest state of the Thing.
+    #[inline]
+    #[must_use]
+    pub fn state(&self) -> &Value {
+        &self.state
+    }
+
+    /// The risks triggered by the latest state, see [`Sifis::evaluate`].
+    #[inline]
+    #[must_use]
+    pub fn risks(&self) -> &[hazard::Risk] {
+        &self.risks
+    }
+
+    /// The highest level of the current risks, or `None` if there are no risks.
+    #[inline]
+    #[must_use]
+    pub fn max_level(&self) -> Option<u8> {
+        self.risks.iter().map(|risk| risk.level).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn home() -> Home<String> {
+        let oven = Sifis::builder()
+            .fire_hazard(5, |cond| cond.when("/properties/temperature").gt(250))
+            .burn(3, |cond| cond.when("/properties/temperature").gt(50))
+            .build();
+        let camera = Sifis::builder()
+            .take_pictures(3, |cond| cond.when("/properties/on").eq(true))
+            .build();
+        let plug = Sifis::builder()
+            .electric_energy_consumption(2, |cond| cond.when("/properties/power").gt(1000))
+            .build();
+
+        [
+            ("oven", oven, json!({ "properties": { "temperature": 20 } })),
+            ("camera", camera, json!({ "properties": { "on": true } })),
+            ("plug", plug, json!({ "properties": { "power": 1500 } })),
+        ]
+        .into_iter()
+        .map(|(thing, sifis, state)| (thing.to_owned(), sifis, state))
+        .collect()
+    }
+
+    #[test]
+    fn queries() {
+        let mut home = home();
+        assert_eq!(home.len(), 3);
+        assert_eq!(
+            home.max_risks(),
+            [
+                hazard::Risk {
+                    id: hazard::Id::TakePictures,
+                    level: 3,
+                },
+                hazard::Risk {
+                    id: hazard::Id::ElectricEnergyConsumption,
+                    level: 2,
+                },
+            ],
+        );
+        assert_eq!(home.exposing(hazard::Category::Privacy), ["camera"]);
+        assert_eq!(home.exposing(hazard::Category::Safety), [&""; 0]);
+        assert_eq!(
+            home.riskiest(5),
+            [(&"camera".to_owned(), 3), (&"plug".to_owned(), 2)]
+        );
+
+        assert_eq!(
+            home.update_state("oven", json!({ "properties": { "temperature": 100 } })),
+            Some(
+                &[hazard::Risk {
+                    id: hazard::Id::Burn,
+                    level: 3,
+                }][..]
+            ),
+        );
+        assert_eq!(home.exposing(hazard::Category::Safety), ["oven"]);
+        assert_eq!(
+            home.riskiest(2),
+            [(&"camera".to_owned(), 3), (&"oven".to_owned(), 3)]
+        );
+
+        home.update_state("oven", json!({ "properties": { "temperature": 300 } }));
+        assert_eq!(home.max_risks()[0].id, hazard::Id::FireHazard);
+        assert_eq!(home.riskiest(1), [(&"oven".to_owned(), 5)]);
+        assert_eq!(home.get("oven").unwrap().max_level(), Some(5));
+
+        assert!(home.update_state("lamp", json!({})).is_none());
+        assert!(home.remove("oven").is_some());
+        assert_eq!(home.exposing(hazard::Category::Safety), [&""; 0]);
+    }
+}
@@ -0,0 +1,849 @@
+//! Incremental evaluation of the risks, driven by changes of the state.
+//!
+//! An [`IncrementalEvaluator`] holds the current state of a Thing and the hazards it triggers.
+//! When the state changes, only the hazards depending on the changed data are evaluated again,
+//! and a [`RiskChanged`] event is emitted for each risk whose level changed.
+//!
+//! The state can be changed one value at a time with [`IncrementalEvaluator::apply_change`], or
+//! using a JSON Patch (RFC 6902) with [`IncrementalEvaluator::apply_patch`].
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{
+//!     hazard,
+//!     incremental::{IncrementalEvaluator, RiskChanged},
+//!     Sifis,
+//! };
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .fire_hazard(7, |cond| cond.when("/properties/brightness").ge(80))
+//!     .build();
+//!
+//! let mut evaluator =
+//!     IncrementalEvaluator::new(&sifis, json!({ "properties": { "brightness": 10 } }));
+//! let brightness = "/properties/brightness".try_into().unwrap();
+//!
+//! assert_eq!(
+//!     evaluator.apply_change(&brightness, json!(50)).unwrap(),
+//!     [RiskChanged {
+//!         id: hazard::Id::FireHazard,
+//!         old_level: None,
+//!         new_level: Some(3),
+//!     }],
+//! );
+//! assert_eq!(evaluator.apply_change(&brightness, json!(60)).unwrap(), []);
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    ops::Not,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    compiled::CompiledSifis,
+    condition::ComparableNumber,
+    hazard::{self, JsonPointer},
+    insert_max_risk, Sifis,
+};
+
+/// An evaluator of the risks that keeps track of the state of a Thing.
+///
+/// See the [module documentation](crate::incremental) for more information.
+#[derive(Debug, Clone)]
+pub struct IncrementalEvaluator {
+    compiled: CompiledSifis,
+    state: Value,
+    triggered: Vec<bool>,
+}
+
+impl IncrementalEvaluator {
+    /// Creates an evaluator for the hazards of a `Sifis` extension, given the initial state.
+    ///
+    /// The state is a JSON document with the same structure of the Thing Description, see
+    /// [`hazard::Condition::is_satisfied_by`] for more information.
+    #[must_use]
+    pub fn new(sifis: &Sifis, state: Value) -> Self {
+        let compiled = sifis.compile();
+        let triggered = (0..compiled.len())
+            .map(|hazard| compiled.is_triggered(hazard, &state))
+            .collect();
+
+        Self {
+            compiled,
+            state,
+            triggered,
+        }
+    }
+
+    /// Returns the current state.
+    #[inline]
+    #[must_use]
+    pub fn state(&self) -> &Value {
+        &self.state
+    }
+
+    /// Returns the risks triggered by the current state.
+    ///
+    /// The result is the same of [`Sifis::evaluate`] on the current state.
+    #[must_use]
+    pub fn risks(&self) -> Vec<hazard::Risk> {
+        self.triggered
+            .iter()
+            .enumerate()
+            .filter(|&(_, &triggered)| triggered)
+            .fold(Vec::new(), |mut risks, (hazard, _)| {
+                insert_max_risk(&mut risks, self.compiled.risk(hazard));
+                risks
+            })
+    }
+
+    /// Replaces the whole state, returning the risks whose level changed.
+    pub fn replace_state(&mut self, state: Value) -> Vec<RiskChanged> {
+        self.state = state;
+        self.update([""])
+    }
+
+    /// Sets the value behind a JSON pointer, returning the risks whose level changed.
+    ///
+    /// Missing object members along the pointer are created, and a value can be appended to an
+    /// array using its length or `-` as index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be set, for instance because the pointer refers to an
+    /// out-of-bounds array element or to a child of a string. The [`operation`] of the error is
+    /// always 0. In case of error the state is left untouched.
+    ///
+    /// [`operation`]: PatchError::operation
+    pub fn apply_change(
+        &mut self,
+        pointer: &JsonPointer,
+        value: Value,
+    ) -> Result<Vec<RiskChanged>, PatchError> {
+        tokens(pointer)
+            .and_then(|tokens| set(&mut self.state, &tokens, value))
+            .map_err(|kind| PatchError { operation: 0, kind })?;
+
+        Ok(self.update([shifted(pointer)]))
+    }
+
+    /// Applies a JSON Patch (RFC 6902) to the state, returning the risks whose level changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the operations fails. In this case none of the operations is
+    /// applied, as required by the RFC.
+    pub fn apply_patch(
+        &mut self,
+        patch: &[PatchOperation],
+    ) -> Result<Vec<RiskChanged>, PatchError> {
+        let mut state = self.state.clone();
+        for (index, operation) in patch.iter().enumerate() {
+            operation.apply(&mut state).map_err(|kind| PatchError {
+                operation: index,
+                kind,
+            })?;
+        }
+        self.state = state;
+
+        let changed = patch.iter().flat_map(|operation| match operation {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Copy { path, .. } => [Some(shifted(path)), None],
+            PatchOperation::Replace { path, .. } => [Some(path.as_ref()), None],
+            PatchOperation::Move { from, path } => [Some(shifted(from)), Some(shifted(path))],
+            PatchOperation::Test { .. } => [None, None],
+        });
+        Ok(self.update(changed.flatten()))
+    }
+
+    /// Evaluates the hazards affected by the changed pointers, returning the changes of risks.
+    fn update<'a, I>(&mut self, changed: I) -> Vec<RiskChanged>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut affected: Vec<usize> = changed
+            .into_iter()
+            .flat_map(|pointer| self.compiled.affected_hazards(pointer))
+            .collect();
+        affected.sort_unstable();
+        affected.dedup();
+
+        let mut ids: Vec<hazard::Id> = Vec::new();
+        for &hazard in &affected {
+            let id = self.compiled.risk(hazard).id;
+            if ids.contains(&id).not() {
+                ids.push(id);
+            }
+        }
+
+        let old_levels: Vec<_> = ids.iter().map(|&id| self.level(id)).collect();
+        for &hazard in &affected {
+            self.triggered[hazard] = self.compiled.is_triggered(hazard, &self.state);
+        }
+
+        ids.into_iter()
+            .zip(old_levels)
+            .filter_map(|(id, old_level)| {
+                let new_level = self.level(id);
+                (old_level != new_level).then_some(RiskChanged {
+                    id,
+                    old_level,
+                    new_level,
+                })
+            })
+            .collect()
+    }
+
+    /// The highest level of the triggered hazards with the given id.
+    fn level(&self, id: hazard::Id) -> Option<u8> {
+        self.triggered
+            .iter()
+            .enumerate()
+            .filter(|&(_, &triggered)| triggered)
+            .map(|(hazard, _)| self.compiled.risk(hazard))
+            .filter(|risk| risk.id == id)
+            .map(|risk| risk.level)
+            .max()
+    }
+}
+
+/// The change of level of a risk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RiskChanged {
+    /// The id of the risk.
+    pub id: hazard::Id,
+
+    /// The previous level, or `None` if the risk was not triggered.
+    pub old_level: Option<u8>,
+
+    /// The current level, or `None` if the risk is not triggered anymore.
+    pub new_level: Option<u8>,
+}
+
+impl RiskChanged {
+    /// Returns `true` if the risk level increased, including a risk that was not triggered.
+    #[inline]
+    #[must_use]
+    pub fn is_increase(&self) -> bool {
+        self.new_level > self.old_level
+    }
+
+    /// Returns the changes between two sets of risks, such as the results of
+    /// [`Sifis::evaluate`](crate::Sifis::evaluate) for two states.
+    ///
+    /// The risks of `before` come first, followed by the newly triggered ones.
+    #[must_use]
+    pub fn between(before: &[hazard::Risk], after: &[hazard::Risk]) -> Vec<Self> {
+        let level = |risks: &[hazard::Risk], id| {
+            risks
+                .iter()
+                .find(|risk| risk.id == id)
+                .map(|risk| risk.level)
+        };
+
+        let old_ids = before.iter().map(|risk| risk.id);
+        let new_ids = after
+            .iter()
+            .map(|risk| risk.id)
+            .filter(|&id| level(before, id).is_none());
+
+        old_ids
+            .chain(new_ids)
+            .filter_map(|id| {
+                let old_level = level(before, id);
+                let new_level = level(after, id);
+                (old_level != new_level).then_some(Self {
+                    id,
+                    old_level,
+                    new_level,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Display for RiskChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = &self.id.risk().name;
+        match (self.old_level, self.new_level) {
+            (Some(old), Some(new)) if new > old => write!(f, "{name} raised from {old} to {new}"),
+            (Some(old), Some(new)) if new < old => write!(f, "{name} lowered from {old} to {new}"),
+            (None, Some(new)) => write!(f, "{name} raised to {new}"),
+            (Some(old), None) => write!(f, "{name} cleared from {old}"),
+            _ => write!(f, "{name} unchanged"),
+        }
+    }
+}
+
+/// An operation of a JSON Patch (RFC 6902).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Adds a value to an object or inserts it into an array.
+    Add {
+        /// The location of the new value.
+        path: JsonPointer,
+
+        /// The value to add.
+        value: Value,
+    },
+
+    /// Removes a value.
+    Remove {
+        /// The location of the value to remove.
+        path: JsonPointer,
+    },
+
+    /// Replaces an existing value.
+    Replace {
+        /// The location of the value to replace.
+        path: JsonPointer,
+
+        /// The new value.
+        value: Value,
+    },
+
+    /// Moves a value from a location to another.
+    Move {
+        /// The location of the value to move.
+        from: JsonPointer,
+
+        /// The new location of the value.
+        path: JsonPointer,
+    },
+
+    /// Copies a value from a location to another.
+    Copy {
+        /// The location of the value to copy.
+        from: JsonPointer,
+
+        /// The location of the copy.
+        path: JsonPointer,
+    },
+
+    /// Tests that a value is equal to the expected one.
+    Test {
+        /// The location of the value to test.
+        path: JsonPointer,
+
+        /// The expected value.
+        value: Value,
+    },
+}
+
+impl PatchOperation {
+    fn apply(&self, state: &mut Value) -> Result<(), PatchErrorKind> {
+        match self {
+            Self::Add { path, value } => add(state, &tokens(path)?, value.clone()),
+            Self::Remove { path } => remove(state, &tokens(path)?).map(drop),
+            Self::Replace { path, value } => {
+                *get_mut(state, &tokens(path)?).ok_or(PatchErrorKind::NotFound)? = value.clone();
+                Ok(())
+            }
+            Self::Move { from, path } => {
+                if path.as_ref() == from.as_ref() {
+                    return get(state, &tokens(from)?)
+                        .map(drop)
+                        .ok_or(PatchErrorKind::NotFound);
+                }
+
+                if path.starts_with(&format!("{}/", &**from)) {
+                    return Err(PatchErrorKind::MoveIntoChild);
+                }
+
+                let value = remove(state, &tokens(from)?)?;
+                add(state, &tokens(path)?, value)
+            }
+            Self::Copy { from, path } => {
+                let value = get(state, &tokens(from)?)
+                    .ok_or(PatchErrorKind::NotFound)?
+                    .clone();
+                add(state, &tokens(path)?, value)
+            }
+            Self::Test { path, value } => {
+                let current = get(state, &tokens(path)?).ok_or(PatchErrorKind::NotFound)?;
+                if json_eq(current, value) {
+                    Ok(())
+                } else {
+                    Err(PatchErrorKind::TestFailed)
+                }
+            }
+        }
+    }
+}
+
+/// The error returned when a JSON Patch cannot be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatchError {
+    /// The index of the failed operation.
+    pub operation: usize,
+
+    /// The reason of the failure.
+    pub kind: PatchErrorKind,
+}
+
+/// The reason of the failure of a JSON Patch operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatchErrorKind {
+    /// A JSON pointer is malformed.
+    InvalidPointer,
+
+    /// The value to change or to read does not exist.
+    NotFound,
+
+    /// The new value cannot be placed in the target location, i.e. because of an array index out
+    /// of bounds.
+    InvalidTarget,
+
+    /// A value cannot be moved into one of its children.
+    MoveIntoChild,
+
+    /// The value of a `test` operation is different from the expected one.
+    TestFailed,
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON patch operation #{} failed: ", self.operation)?;
+        f.write_str(match self.kind {
+            PatchErrorKind::InvalidPointer => "malformed JSON pointer",
+            PatchErrorKind::NotFound => "value not found",
+            PatchErrorKind::InvalidTarget => "invalid target location",
+            PatchErrorKind::MoveIntoChild => "cannot move a value into one of its children",
+            PatchErrorKind::TestFailed => "test failed",
+        })
+    }
+}
+
+impl Error for PatchError {}
+
+fn tokens(pointer: &JsonPointer) -> Result<Vec<String>, PatchErrorKind> {
+    if pointer.0.is_valid() {
+        Ok(pointer
+            .0
+            .tokens()
+            .map(|token| token.decoded().to_owned())
+            .collect())
+    } else {
+        Err(PatchErrorKind::InvalidPointer)
+    }
+}
+
+/// The pointer whose hazards are affected by inserting or removing the value behind `pointer`.
+///
+/// Inserting or removing an array element shifts the following ones, and appending creates a new
+/// index, therefore the whole array is affected. The last token is only checked syntactically, so
+/// an object member named like an index affects its siblings too, which is harmless.
+fn shifted(pointer: &JsonPointer) -> &str {
+    let pointer: &str = pointer.as_ref();
+    match pointer.rsplit_once('/') {
+        Some((parent, last)) if last == "-" || parse_index(last).is_some() => parent,
+        _ => pointer,
+    }
+}
+
+/// Parses an array index, as defined by RFC 6901.
+fn parse_index(token: &str) -> Option<usize> {
+    let valid =
+        token.bytes().all(|c| c.is_ascii_digit()) && (token == "0" || token.starts_with('0').not());
+    valid.then(|| token.parse().ok()).flatten()
+}
+
+fn get<'a>(mut value: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    for token in tokens {
+        value = match value {
+            Value::Object(map) => map.get(token)?,
+            Value::Array(array) => array.get(parse_index(token)?)?,
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+fn get_mut<'a>(mut value: &'a mut Value, tokens: &[String]) -> Option<&'a mut Value> {
+    for token in tokens {
+        value = match value {
+            Value::Object(map) => map.get_mut(token)?,
+            Value::Array(array) => array.get_mut(parse_index(token)?)?,
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+fn add(state: &mut Value, tokens: &[String], value: Value) -> Result<(), PatchErrorKind> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *state = value;
+        return Ok(());
+    };
+
+    match get_mut(state, parent).ok_or(PatchErrorKind::NotFound)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(array) => {
+            let index = if last == "-" {
+                array.len()
+            } else {
+                parse_index(last)
+                    .filter(|&index| index <= array.len())
+                    .ok_or(PatchErrorKind::InvalidTarget)?
+            };
+            array.insert(index, value);
+        }
+        _ => return Err(PatchErrorKind::InvalidTarget),
+    }
+
+    Ok(())
+}
+
+/// Sets a value, creating the missing object members along the way.
+///
+/// Errors can only occur before the state is changed.
+fn set(state: &mut Value, tokens: &[String], value: Value) -> Result<(), PatchErrorKind> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *state = value;
+        return Ok(());
+    };
+
+    let mut current = state;
+    for token in parent {
+        current = match current {
+            Value::Object(map) => map
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(serde_json::Map::new())),
+            Value::Array(array) => parse_index(token)
+                .and_then(|index| array.get_mut(index))
+                .ok_or(PatchErrorKind::InvalidTarget)?,
+            _ => return Err(PatchErrorKind::InvalidTarget),
+        };
+    }
+
+    match current {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(array) => {
+            let index = if last == "-" {
+                array.len()
+            } else {
+                parse_index(last)
+                    .filter(|&index| index <= array.len())
+                    .ok_or(PatchErrorKind::InvalidTarget)?
+            };
+
+            if let Some(cur) = array.get_mut(index) {
+                *cur = value;
+            } else {
+                array.push(value);
+            }
+        }
+        _ => return Err(PatchErrorKind::InvalidTarget),
+    }
+
+    Ok(())
+}
+
+fn remove(state: &mut Value, tokens: &[String]) -> Result<Value, PatchErrorKind> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Ok(std::mem::take(state));
+    };
+
+    match get_mut(state, parent).ok_or(PatchErrorKind::NotFound)? {
+        Value::Object(map) => map.remove(last).ok_or(PatchErrorKind::NotFound),
+        Value::Array(array) => parse_index(last)
+            .filter(|&index| index < array.len())
+            .map(|index| array.remove(index))
+            .ok_or(PatchErrorKind::NotFound),
+        _ => Err(PatchErrorKind::NotFound),
+    }
+}
+
+/// Compares two JSON values, considering numbers equal when they have the same numeric value.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => {
+            ComparableNumber::from(a) == ComparableNumber::from(b)
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_eq(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sifis() -> Sifis {
+        Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+            .fire_hazard(7, |cond| {
+                cond.when("/properties/brightness")
+                    .ge(80)
+                    .and("/properties/on")
+                    .eq(true)
+            })
+            .take_pictures(2, |cond| cond.when("/properties/camera/on").eq(true))
+            .build()
+    }
+
+    fn fire(old_level: Option<u8>, new_level: Option<u8>) -> RiskChanged {
+        RiskChanged {
+            id: hazard::Id::FireHazard,
+            old_level,
+            new_level,
+        }
+    }
+
+    #[test]
+    fn apply_changes() {
+        let sifis = sifis();
+        let mut evaluator = IncrementalEvaluator::new(&sifis, json!({}));
+        assert_eq!(evaluator.risks(), []);
+
+        let mut change = |pointer: &str, value| {
+            let changes = evaluator
+                .apply_change(&pointer.try_into().unwrap(), value)
+                .unwrap();
+            assert_eq!(evaluator.risks(), sifis.evaluate(evaluator.state()));
+            changes
+        };
+
+        assert_eq!(change("/properties/on", json!(true)), []);
+        assert_eq!(
+            change("/properties/brightness", json!(90)),
+            [fire(None, Some(7))],
+        );
+        assert_eq!(
+            change("/properties/on", json!(false)),
+            [fire(Some(7), Some(3))]
+        );
+        assert_eq!(
+            change("/properties/camera", json!({ "on": true })),
+            [RiskChanged {
+                id: hazard::Id::TakePictures,
+                old_level: None,
+                new_level: Some(2),
+            }],
+        );
+        assert_eq!(
+            change("/properties", json!({ "brightness": 0 })),
+            [
+                fire(Some(3), None),
+                RiskChanged {
+                    id: hazard::Id::TakePictures,
+                    old_level: Some(2),
+                    new_level: None,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn apply_patch() {
+        let sifis = sifis();
+        let mut evaluator = IncrementalEvaluator::new(
+            &sifis,
+            json!({ "properties": { "brightness": 50, "on": false } }),
+        );
+        assert_eq!(
+            evaluator.risks(),
+            [hazard::Risk {
+                id: hazard::Id::FireHazard,
+                level: 3,
+            }],
+        );
+
+        let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+            { "op": "test", "path": "/properties/brightness", "value": 50.0 },
+            { "op": "replace", "path": "/properties/brightness", "value": 85 },
+            { "op": "add", "path": "/properties/on", "value": true },
+            { "op": "add", "path": "/properties/camera", "value": { "list": [] } },
+            { "op": "add", "path": "/properties/camera/list/-", "value": 1 },
+            { "op": "copy", "from": "/properties/on", "path": "/properties/camera/on" },
+        ]))
+        .unwrap();
+        assert_eq!(
+            evaluator.apply_patch(&patch).unwrap(),
+            [
+                fire(Some(3), Some(7)),
+                RiskChanged {
+                    id: hazard::Id::TakePictures,
+                    old_level: None,
+                    new_level: Some(2),
+                },
+            ],
+        );
+        assert_eq!(
+            evaluator.state(),
+            &json!({
+                "properties": {
+                    "brightness": 85,
+                    "on": true,
+                    "camera": { "list": [1], "on": true },
+                },
+            }),
+        );
+
+        let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+            { "op": "move", "from": "/properties/on", "path": "/properties/off" },
+            { "op": "remove", "path": "/properties/camera/list/0" },
+        ]))
+        .unwrap();
+        assert_eq!(
+            evaluator.apply_patch(&patch).unwrap(),
+            [fire(Some(7), Some(3))]
+        );
+        assert_eq!(evaluator.risks(), sifis.evaluate(evaluator.state()));
+    }
+
+    #[test]
+    fn array_changes() {
+        let sifis = Sifis::builder()
+            .fire_hazard(7, |cond| cond.when("/properties/list/1").eq(true))
+            .build();
+        let mut evaluator =
+            IncrementalEvaluator::new(&sifis, json!({ "properties": { "list": [false] } }));
+
+        let mut change = |pointer: &str, value| {
+            let changes = evaluator
+                .apply_change(&pointer.try_into().unwrap(), value)
+                .unwrap();
+            assert_eq!(evaluator.risks(), sifis.evaluate(evaluator.state()));
+            changes
+        };
+        assert_eq!(
+            change("/properties/list/-", json!(true)),
+            [fire(None, Some(7))]
+        );
+        assert_eq!(
+            change("/properties/list/1", json!(false)),
+            [fire(Some(7), None)]
+        );
+        assert_eq!(change("/properties/list/2", json!(true)), []);
+
+        let patches = [
+            (
+                json!({ "op": "remove", "path": "/properties/list/0" }),
+                Some(7),
+            ),
+            (
+                json!({ "op": "add", "path": "/properties/list/0", "value": true }),
+                None,
+            ),
+            (
+                json!({ "op": "add", "path": "/properties/list/-", "value": true }),
+                None,
+            ),
+            (
+                json!({ "op": "remove", "path": "/properties/list/1" }),
+                Some(7),
+            ),
+            (
+                json!({ "op": "add", "path": "/properties/list/1", "value": false }),
+                None,
+            ),
+            (
+                json!({ "op": "move", "from": "/properties/list/1", "path": "/properties/other" }),
+                Some(7),
+            ),
+            (
+                json!({ "op": "copy", "from": "/properties/other", "path": "/properties/list/1" }),
+                None,
+            ),
+            (
+                json!({ "op": "move", "from": "/properties/list/0", "path": "/properties/list/2" }),
+                Some(7),
+            ),
+        ];
+        for (operation, level) in patches {
+            let patch: Vec<PatchOperation> = serde_json::from_value(json!([operation])).unwrap();
+            evaluator.apply_patch(&patch).unwrap();
+            assert_eq!(evaluator.risks(), sifis.evaluate(evaluator.state()));
+            assert_eq!(evaluator.level(hazard::Id::FireHazard), level);
+        }
+    }
+
+    #[test]
+    fn failing_patch() {
+        let state = json!({ "properties": { "brightness": 50, "list": [1, 2] } });
+        let mut evaluator = IncrementalEvaluator::new(&sifis(), state.clone());
+
+        let failures = [
+            (
+                json!({ "op": "test", "path": "/properties/brightness", "value": 51 }),
+                PatchErrorKind::TestFailed,
+            ),
+            (
+                json!({ "op": "remove", "path": "/properties/on" }),
+                PatchErrorKind::NotFound,
+            ),
+            (
+                json!({ "op": "replace", "path": "/properties/list/01", "value": 1 }),
+                PatchErrorKind::NotFound,
+            ),
+            (
+                json!({ "op": "add", "path": "/properties/list/3", "value": 1 }),
+                PatchErrorKind::InvalidTarget,
+            ),
+            (
+                json!({ "op": "add", "path": "/missing/value", "value": 1 }),
+                PatchErrorKind::NotFound,
+            ),
+            (
+                json!({ "op": "move", "from": "/properties", "path": "/properties/inner" }),
+                PatchErrorKind::MoveIntoChild,
+            ),
+        ];
+
+        for (operation, kind) in failures {
+            let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+                { "op": "replace", "path": "/properties/brightness", "value": 90 },
+                operation,
+            ]))
+            .unwrap();
+
+            assert_eq!(
+                evaluator.apply_patch(&patch),
+                Err(PatchError { operation: 1, kind }),
+            );
+            assert_eq!(evaluator.state(), &state);
+        }
+
+        for pointer in ["/properties/list/5", "/properties/brightness/value"] {
+            assert_eq!(
+                evaluator
+                    .apply_change(&pointer.try_into().unwrap(), json!(1))
+                    .unwrap_err()
+                    .to_string(),
+                "JSON patch operation #0 failed: invalid target location",
+            );
+            assert_eq!(evaluator.state(), &state);
+        }
+
+        evaluator
+            .apply_change(&"/properties/list/-".try_into().unwrap(), json!(3))
+            .unwrap();
+        evaluator
+            .apply_change(&"/properties/list/0".try_into().unwrap(), json!(0))
+            .unwrap();
+        assert_eq!(evaluator.state()["properties"]["list"], json!([0, 2, 3]));
+    }
+}
@@ -12,9 +12,26 @@
 //! [wot-td]: wot_td
 //! [`Thing`]: wot_td::Thing
 //!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it the crate is `no_std` and it only requires
+//! `alloc`: the data model in [`hazard`], [`condition`], [`risk`] and [`borrowed`] can be
+//! deserialized and evaluated with [`Sifis::evaluate`], while the integration with wot-td and the
+//! remaining modules are not available.
+//!
+//! # Binary encodings
+//!
+//! The structures can be exchanged using self-describing binary formats like CBOR or
+//! `MessagePack`, which are lighter than JSON on constrained networks. The fields must be encoded
+//! with their names, i.e. using `rmp_serde::to_vec_named` instead of `rmp_serde::to_vec`, because
+//! empty fields are skipped. The ids of the hazards can be encoded as integers in custom
+//! structures with [`hazard::id_code`].
+//!
 //! # Example
 //!
 //! ```
+//! # #[cfg(feature = "std")]
+//! # fn main() {
 //! use serde_json::json;
 //! use sifis_td::{
 //!     condition::{Condition, self},
@@ -176,19 +193,66 @@
 //!         ],
 //!     }),
 //! )
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
 //! ```
 
 #![warn(clippy::pedantic)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+pub mod borrowed;
+#[cfg(feature = "std")]
+pub mod budget;
+#[cfg(feature = "std")]
 pub mod builder;
+#[cfg(feature = "std")]
+pub mod compiled;
+#[cfg(feature = "std")]
+pub mod compound;
 pub mod condition;
+#[cfg(feature = "std")]
+pub mod diff;
+mod domain;
 pub mod hazard;
+#[cfg(feature = "std")]
+pub mod home;
+#[cfg(feature = "std")]
+pub mod incremental;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "async")]
+pub mod monitor;
+#[cfg(feature = "std")]
+pub mod policy;
+#[cfg(feature = "std")]
+pub mod predict;
+#[cfg(feature = "std")]
+pub mod render;
 pub mod risk;
+#[cfg(feature = "std")]
+pub mod risk_map;
+#[cfg(feature = "schemars")]
+pub mod schema;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod witness;
+
+use alloc::vec::Vec;
 
+#[cfg(feature = "std")]
 pub use builder::Builder;
+#[cfg(feature = "std")]
+pub use diff::diff;
 use hazard::Hazard;
 use serde::{Deserialize, Serialize};
-use wot_td::extend::ExtendableThing;
 
 /// The Sifis extension for a Thing Description.
 ///
@@ -197,6 +261,7 @@ use wot_td::extend::ExtendableThing;
 ///
 /// [`Thing`]: wot_td::Thing
 #[derive(Debug, Default, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Sifis {
     /// A set of possible risks used by the thing.
     #[serde(skip_serializing_if = "Vec::is_empty", rename = "sho:risks", default)]
@@ -214,14 +279,75 @@ pub struct Sifis {
 
 impl Sifis {
     /// Creates a [`Builder`] for the `Sifis` extension.
+    #[cfg(feature = "std")]
     #[inline]
     #[must_use]
     pub fn builder() -> Builder {
         Builder::default()
     }
+
+    /// Evaluates the risks triggered by the state of a Thing.
+    ///
+    /// The state is a JSON document with the same structure of the Thing Description, see
+    /// [`hazard::Condition::is_satisfied_by`] for more information.
+    ///
+    /// When multiple hazards with the same [`Id`] are triggered, only the highest level is
+    /// returned. The risks are sorted by the first triggered hazard in [`hazards`] with their id.
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use sifis_td::{hazard, Sifis};
+    ///
+    /// let sifis: Sifis = serde_json::from_value(json!({
+    ///     "sho:hazards": [{
+    ///         "sho:risk": { "@id": "sho:FireHazard", "sho:level": 3 },
+    ///         "sho:conditions": [[{
+    ///             "sho:pointer": "/properties/brightness",
+    ///             "sho:condition": { "sho:value": 40, "sho:op": "ge" }
+    ///         }]]
+    ///     }, {
+    ///         "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+    ///         "sho:conditions": [[{
+    ///             "sho:pointer": "/properties/brightness",
+    ///             "sho:condition": { "sho:value": 80, "sho:op": "ge" }
+    ///         }]]
+    ///     }]
+    /// }))
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     sifis.evaluate(&json!({ "properties": { "brightness": 90 } })),
+    ///     [hazard::Risk {
+    ///         id: hazard::Id::FireHazard,
+    ///         level: 7,
+    ///     }],
+    /// );
+    /// ```
+    ///
+    /// [`Id`]: hazard::Id
+    /// [`hazards`]: Sifis::hazards
+    #[must_use]
+    pub fn evaluate(&self, state: &serde_json::Value) -> Vec<hazard::Risk> {
+        self.hazards
+            .iter()
+            .filter(|hazard| hazard.is_triggered_by(state))
+            .fold(Vec::new(), |mut risks, hazard| {
+                insert_max_risk(&mut risks, hazard.risk);
+                risks
+            })
+    }
+}
+
+/// Adds the risk to the set, keeping only the highest level for each id.
+pub(crate) fn insert_max_risk(risks: &mut Vec<hazard::Risk>, risk: hazard::Risk) {
+    match risks.iter_mut().find(|cur| cur.id == risk.id) {
+        Some(cur) => cur.level = cur.level.max(risk.level),
+        None => risks.push(risk),
+    }
 }
 
-impl ExtendableThing for Sifis {
+#[cfg(feature = "std")]
+impl wot_td::extend::ExtendableThing for Sifis {
     type InteractionAffordance = ();
     type PropertyAffordance = ();
     type ActionAffordance = ();
@@ -235,16 +361,23 @@ impl ExtendableThing for Sifis {
 
 #[cfg(test)]
 mod tests {
-    use std::cmp::Ordering;
+    use alloc::vec;
 
     use jsonptr::Resolve;
-    use serde_json::{json, Number};
+    use serde_json::json;
 
-    use crate::condition::Condition;
+    use crate::condition::{ComparableNumber, Condition};
 
     use super::*;
 
-    fn evaluate_hazards<T>(sifis: &Sifis, thing_data: &T) -> Vec<hazard::Risk>
+    /// Evaluates the hazards with `naive_evaluate`, checking that `Sifis::evaluate` agrees.
+    fn evaluate_hazards(sifis: &Sifis, thing_data: &serde_json::Value) -> Vec<hazard::Risk> {
+        let risks = naive_evaluate(sifis, thing_data);
+        assert_eq!(sifis.evaluate(thing_data), risks);
+        risks
+    }
+
+    fn naive_evaluate<T>(sifis: &Sifis, thing_data: &T) -> Vec<hazard::Risk>
     where
         T: Resolve,
     {
@@ -276,7 +409,7 @@ mod tests {
                         }
 
                         Ok((Value::Number(n1), Condition::Value(condition::Value::Number(n2)))) => {
-                            ComparableNumber(n1) == ComparableNumber(n2)
+                            ComparableNumber::from(n1) == ComparableNumber::from(n2)
                         }
 
                         Ok((
@@ -285,7 +418,11 @@ mod tests {
                                 value: condition::Value::Number(n2),
                                 op,
                             }),
-                        )) => apply_op(&ComparableNumber(n1), &ComparableNumber(n2), *op),
+                        )) => apply_op(
+                            &ComparableNumber::from(n1),
+                            &ComparableNumber::from(n2),
+                            *op,
+                        ),
 
                         Ok((Value::Number(_), _)) => {
                             panic!("invalid comparison, {ptr} is unexpectedly a number")
@@ -344,47 +481,71 @@ mod tests {
         }
     }
 
-    #[derive(Debug, Clone)]
-    struct ComparableNumber<'a>(&'a Number);
-
-    impl PartialEq for ComparableNumber<'_> {
-        fn eq(&self, other: &Self) -> bool {
-            if let Some((a, b)) = self.0.as_u64().zip(other.0.as_u64()) {
-                return a == b;
-            }
-
-            if let Some((a, b)) = self.0.as_i64().zip(other.0.as_i64()) {
-                return a == b;
-            }
-
-            let a = self.0.as_f64().unwrap();
-            let b = other.0.as_f64().unwrap();
-            a == b
-        }
-    }
-
-    impl Eq for ComparableNumber<'_> {}
-
-    impl Ord for ComparableNumber<'_> {
-        fn cmp(&self, other: &Self) -> Ordering {
-            if let Some((a, b)) = self.0.as_u64().zip(other.0.as_u64()) {
-                return a.cmp(&b);
-            }
-
-            if let Some((a, b)) = self.0.as_i64().zip(other.0.as_i64()) {
-                return a.cmp(&b);
-            }
-
-            let a = self.0.as_f64().unwrap();
-            let b = other.0.as_f64().unwrap();
-            a.partial_cmp(&b).unwrap()
-        }
-    }
+    #[test]
+    fn evaluate_state() {
+        // The builder is not available without `std`.
+        let sifis: Sifis = serde_json::from_value(json!({
+            "sho:hazards": [
+                {
+                    "sho:risk": { "@id": "sho:FireHazard", "sho:level": 3 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/level",
+                        "sho:condition": { "sho:value": 3, "sho:op": "ge" },
+                    }]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:Explosion", "sho:level": 1 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/on",
+                        "sho:condition": true,
+                    }]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+                    "sho:conditions": [
+                        [{
+                            "sho:pointer": "/properties/level",
+                            "sho:condition": { "sho:value": 5.5, "sho:op": "ge" },
+                        }],
+                        [{
+                            "sho:pointer": "/properties/mode",
+                            "sho:condition": "turbo",
+                        }],
+                    ],
+                },
+            ],
+        }))
+        .unwrap();
 
-    impl PartialOrd for ComparableNumber<'_> {
-        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-            Some(self.cmp(other))
-        }
+        let evaluate = |state| sifis.evaluate(&state);
+        assert_eq!(evaluate(json!({})), []);
+        assert_eq!(
+            evaluate(json!({ "properties": { "level": 4, "on": "true" } })),
+            [hazard::Risk {
+                id: hazard::Id::FireHazard,
+                level: 3,
+            }],
+        );
+        assert_eq!(
+            evaluate(json!({ "properties": { "on": true, "mode": "turbo" } })),
+            [
+                hazard::Risk {
+                    id: hazard::Id::Explosion,
+                    level: 1,
+                },
+                hazard::Risk {
+                    id: hazard::Id::FireHazard,
+                    level: 7,
+                },
+            ],
+        );
+        assert_eq!(
+            evaluate(json!({ "properties": { "level": 5.5 } })),
+            [hazard::Risk {
+                id: hazard::Id::FireHazard,
+                level: 7,
+            }],
+        );
     }
 
     #[test]
@@ -467,9 +628,99 @@ mod tests {
         );
     }
 
-    // The reason behind this test is we don't actually have any implementation to evaluate
-    // the resulting hazards & risk levels given some data. What we are trying to test is, given a
-    // pretty simple testing evaluator, the expressiveness of the `Sifis` structure.
+    fn cbor_round_trip<T>(value: &T) -> T
+    where
+        T: Serialize + serde::de::DeserializeOwned,
+    {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(value, &mut cbor).unwrap();
+        ciborium::from_reader(&*cbor).unwrap()
+    }
+
+    fn msgpack_round_trip<T>(value: &T) -> T
+    where
+        T: Serialize + serde::de::DeserializeOwned,
+    {
+        rmp_serde::from_slice(&rmp_serde::to_vec_named(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let sifis = Sifis {
+            risks: vec![risk::FIRE_HAZARD, risk::SPEND_MONEY],
+            hazards: vec![
+                Hazard {
+                    risk: hazard::Risk {
+                        id: hazard::Id::FireHazard,
+                        level: 7,
+                    },
+                    conditions: vec![
+                        vec![
+                            hazard::Condition {
+                                pointer: "/properties/level".try_into().unwrap(),
+                                condition: Condition::Expr(condition::Expr {
+                                    value: condition::Value::try_from(4.5).unwrap(),
+                                    op: condition::Operation::Ge,
+                                }),
+                            },
+                            hazard::Condition {
+                                pointer: "/properties/on".try_into().unwrap(),
+                                condition: Condition::Value(true.into()),
+                            },
+                        ],
+                        vec![hazard::Condition {
+                            pointer: "/properties/mode".try_into().unwrap(),
+                            condition: Condition::Value("turbo".into()),
+                        }],
+                    ],
+                },
+                Hazard {
+                    risk: hazard::Risk {
+                        id: hazard::Id::SpendMoney,
+                        level: 2,
+                    },
+                    conditions: vec![vec![hazard::Condition {
+                        pointer: "/actions/buy/amount".try_into().unwrap(),
+                        condition: Condition::Expr(condition::Expr {
+                            value: (-3).into(),
+                            op: condition::Operation::Lt,
+                        }),
+                    }]],
+                },
+                Hazard {
+                    risk: hazard::Risk {
+                        id: hazard::Id::Scald,
+                        level: 1,
+                    },
+                    conditions: Vec::new(),
+                },
+            ],
+        };
+
+        for sifis in [sifis.clone(), Sifis::default()] {
+            assert_eq!(cbor_round_trip(&sifis), sifis);
+            assert_eq!(msgpack_round_trip(&sifis), sifis);
+        }
+
+        for hazard in &sifis.hazards {
+            assert_eq!(&cbor_round_trip(hazard), hazard);
+            assert_eq!(&msgpack_round_trip(hazard), hazard);
+
+            for condition in hazard.conditions.iter().flatten() {
+                let condition = &condition.condition;
+                assert_eq!(&cbor_round_trip(condition), condition);
+                assert_eq!(&msgpack_round_trip(condition), condition);
+            }
+        }
+
+        for detail in risk::ALL {
+            assert_eq!(&cbor_round_trip(detail), detail);
+            assert_eq!(&msgpack_round_trip(detail), detail);
+        }
+    }
+
+    // What we are trying to test is, given a pretty simple testing evaluator, the expressiveness of
+    // the `Sifis` structure. Each evaluation is also checked against `Sifis::evaluate`.
     #[test]
     #[allow(clippy::too_many_lines)]
     fn resolve_hazards() {
@@ -0,0 +1,313 @@
+//! Declaration of the risks an application may cause.
+//!
+//! An [`AppManifest`] lists the risks an application requests, with their maximum levels, and the
+//! Things and affordances it interacts with. [`AppManifest::check`] compares it against the
+//! [`Sifis`] extension of each Thing, in order to find the risks the application could actually
+//! cause and whether they have been declared.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{hazard, manifest::AppManifest, Sifis};
+//!
+//! let manifest: AppManifest = serde_json::from_value(json!({
+//!     "name": "night light",
+//!     "hazards": [{ "@id": "sho:FireHazard", "sho:level": 5 }],
+//!     "things": [{ "thing": "lamp", "affordances": ["/properties/brightness"] }],
+//! }))
+//! .unwrap();
+//!
+//! let lamp = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .fire_hazard(7, |cond| cond.when("/properties/brightness").ge(80))
+//!     .take_pictures(5, |cond| cond.when("/properties/camera").eq(true))
+//!     .build();
+//!
+//! let report = manifest.check(|thing| (thing == "lamp").then_some(&lamp));
+//! assert!(report.declared.is_empty());
+//! assert_eq!(report.undeclared[0].risk.level, 7);
+//! assert_eq!(report.undeclared[0].declared_level, Some(5));
+//! ```
+
+use std::{borrow::Borrow, ops::Not};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hazard::{self, JsonPointer},
+    home::{Entry, Home},
+    insert_max_risk, Sifis,
+};
+
+/// The risks requested by an application and the Things it interacts with.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AppManifest {
+    /// The name of the application.
+    pub name: String,
+
+    /// The risks the application may cause, with their maximum levels.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub hazards: Vec<hazard::Risk>,
+
+    /// The Things the application interacts with.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub things: Vec<ThingAccess>,
+}
+
+/// The interaction of an application with a Thing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ThingAccess {
+    /// The id of the Thing.
+    pub thing: String,
+
+    /// The JSON pointers of the data the application can change, relative to the Thing
+    /// Description (i.e.: `/properties/brightness` or `/actions/toggle`).
+    ///
+    /// When empty, the application can change any data of the Thing.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub affordances: Vec<JsonPointer>,
+}
+
+/// The outcome of [`AppManifest::check`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ManifestReport {
+    /// The risks that the application could cause and that are declared in the manifest.
+    pub declared: Vec<PotentialRisk>,
+
+    /// The risks that the application could cause but that are not declared in the manifest, or
+    /// that have a level higher than the declared one.
+    pub undeclared: Vec<PotentialRisk>,
+
+    /// The Things listed in the manifest whose `Sifis` extension is not available.
+    pub unknown_things: Vec<String>,
+}
+
+/// A risk an application could cause on a Thing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PotentialRisk {
+    /// The id of the Thing.
+    pub thing: String,
+
+    /// The risk, with the highest level among the hazards that can be triggered.
+    pub risk: hazard::Risk,
+
+    /// The maximum level declared in the manifest for the risk, if any.
+    pub declared_level: Option<u8>,
+}
+
+impl AppManifest {
+    /// Returns the maximum level declared for a risk, if any.
+    #[must_use]
+    pub fn declared_level(&self, id: hazard::Id) -> Option<u8> {
+        self.hazards
+            .iter()
+            .filter(|risk| risk.id == id)
+            .map(|risk| risk.level)
+            .max()
+    }
+
+    /// Computes the risks the application could cause on the Things it interacts with.
+    ///
+    /// `sifis` returns the `Sifis` extension of a Thing given its id. The application could
+    /// cause the risks of the hazards whose conditions refer to the affordances it can change,
+    /// their ancestors or their descendants; see [`CompiledSifis::affected_hazards`].
+    ///
+    /// [`CompiledSifis::affected_hazards`]: crate::compiled::CompiledSifis::affected_hazards
+    #[must_use]
+    pub fn check<'a, F>(&self, mut sifis: F) -> ManifestReport
+    where
+        F: FnMut(&str) -> Option<&'a Sifis>,
+    {
+        let mut report = ManifestReport::default();
+
+        for access in &self.things {
+            let Some(sifis) = sifis(&access.thing) else {
+                report.unknown_things.push(access.thing.clone());
+                continue;
+            };
+
+            let compiled = sifis.compile();
+            let mut hazards: Vec<usize> = if access.affordances.is_empty() {
+                (0..compiled.len()).collect()
+            } else {
+                access
+                    .affordances
+                    .iter()
+                    .flat_map(|pointer| compiled.affected_hazards(pointer.as_ref()))
+                    .collect()
+            };
+            hazards.sort_unstable();
+            hazards.dedup();
+
+            let risks = hazards
+                .into_iter()
+                .filter(|&hazard| sifis.hazards[hazard].conditions.is_empty().not())
+                .fold(Vec::new(), |mut risks, hazard| {
+                    insert_max_risk(&mut risks, compiled.risk(hazard));
+                    risks
+                });
+
+            for risk in risks {
+                let declared_level = self.declared_level(risk.id);
+                let potential = PotentialRisk {
+                    thing: access.thing.clone(),
+                    risk,
+                    declared_level,
+                };
+
+                if declared_level.is_some_and(|level| risk.level <= level) {
+                    report.declared.push(potential);
+                } else {
+                    report.undeclared.push(potential);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Computes the risks the application could cause on the Things of a home.
+    ///
+    /// See [`check`] for more information.
+    ///
+    /// [`check`]: AppManifest::check
+    #[must_use]
+    pub fn check_home<K>(&self, home: &Home<K>) -> ManifestReport
+    where
+        K: Ord + Borrow<str>,
+    {
+        self.check(|thing| home.get(thing).map(Entry::sifis))
+    }
+}
+
+impl ManifestReport {
+    /// Returns `true` if the application cannot cause any undeclared risk and all its Things are
+    /// known.
+    #[inline]
+    #[must_use]
+    pub fn is_compliant(&self) -> bool {
+        self.undeclared.is_empty() && self.unknown_things.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn check() {
+        let manifest = AppManifest {
+            name: "kitchen".to_owned(),
+            hazards: vec![
+                hazard::Risk {
+                    id: hazard::Id::Burn,
+                    level: 5,
+                },
+                hazard::Risk {
+                    id: hazard::Id::ElectricEnergyConsumption,
+                    level: 2,
+                },
+            ],
+            things: vec![
+                ThingAccess {
+                    thing: "oven".to_owned(),
+                    affordances: vec!["/properties/temperature".try_into().unwrap()],
+                },
+                ThingAccess {
+                    thing: "plug".to_owned(),
+                    affordances: vec![],
+                },
+                ThingAccess {
+                    thing: "fridge".to_owned(),
+                    affordances: vec![],
+                },
+            ],
+        };
+
+        let mut home = Home::new();
+        home.insert(
+            "oven",
+            Sifis::builder()
+                .burn(3, |cond| cond.when("/properties/temperature").gt(50))
+                .fire_hazard(6, |cond| cond.when("/properties/temperature").gt(250))
+                .take_pictures(2, |cond| cond.when("/properties/camera").eq(true))
+                .build(),
+            json!({}),
+        );
+        home.insert(
+            "plug",
+            Sifis::builder()
+                .electric_energy_consumption(4, |cond| cond.when("/properties/power").gt(1000))
+                .power_outage(1, |cond| cond.when("/actions/off").eq(true))
+                .build(),
+            json!({}),
+        );
+
+        let report = manifest.check_home(&home);
+        assert_eq!(
+            report.declared,
+            [PotentialRisk {
+                thing: "oven".to_owned(),
+                risk: hazard::Risk {
+                    id: hazard::Id::Burn,
+                    level: 3,
+                },
+                declared_level: Some(5),
+            }],
+        );
+        assert_eq!(
+            report.undeclared,
+            [
+                PotentialRisk {
+                    thing: "oven".to_owned(),
+                    risk: hazard::Risk {
+                        id: hazard::Id::FireHazard,
+                        level: 6,
+                    },
+                    declared_level: None,
+                },
+                PotentialRisk {
+                    thing: "plug".to_owned(),
+                    risk: hazard::Risk {
+                        id: hazard::Id::ElectricEnergyConsumption,
+                        level: 4,
+                    },
+                    declared_level: Some(2),
+                },
+                PotentialRisk {
+                    thing: "plug".to_owned(),
+                    risk: hazard::Risk {
+                        id: hazard::Id::PowerOutage,
+                        level: 1,
+                    },
+                    declared_level: None,
+                },
+            ],
+        );
+        assert_eq!(report.unknown_things, ["fridge"]);
+        assert!(report.is_compliant().not());
+    }
+
+    #[test]
+    fn serde() {
+        let manifest = AppManifest {
+            name: "viewer".to_owned(),
+            hazards: vec![],
+            things: vec![ThingAccess {
+                thing: "camera".to_owned(),
+                affordances: vec!["/actions/snapshot".try_into().unwrap()],
+            }],
+        };
+        let value = json!({
+            "name": "viewer",
+            "things": [{ "thing": "camera", "affordances": ["/actions/snapshot"] }],
+        });
+
+        assert_eq!(serde_json::to_value(&manifest).unwrap(), value);
+        assert_eq!(
+            serde_json::from_value::<AppManifest>(value).unwrap(),
+            manifest
+        );
+    }
+}
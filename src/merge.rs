@@ -0,0 +1,285 @@
+//! Composition of multiple [`Sifis`] extensions.
+//!
+//! A _Thing_ can be assembled from different modules (i.e.: a thermostat with a camera
+//! sub-device), each one describing its own set of hazards. The functions in this module allow to
+//! combine them into a single [`Sifis`] extension:
+//!
+//! - [`Sifis::rebase`] moves the JSON pointers of the hazards under a different base, in order to
+//!   _mount_ a sub-device inside the affordances of the main Thing;
+//! - [`Sifis::merge`] adds the risks and the hazards of another extension, deduplicating the
+//!   risks by their [`Id`].
+//!
+//! ```
+//! use sifis_td::{risk, Sifis};
+//!
+//! let mut thermostat = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/temperature").ge(80))
+//!     .build();
+//!
+//! let mut camera = Sifis::builder()
+//!     .take_pictures(5, |cond| cond.when("/properties/on").eq(true))
+//!     .fire_hazard(1, |cond| cond.when("/properties/on").eq(true))
+//!     .build();
+//! camera.rebase(
+//!     &"/properties".try_into().unwrap(),
+//!     &"/properties/camera".try_into().unwrap(),
+//! );
+//!
+//! thermostat.merge(camera).unwrap();
+//! assert_eq!(thermostat.risks, [risk::FIRE_HAZARD, risk::TAKE_PICTURES]);
+//! assert_eq!(
+//!     &*thermostat.hazards[1].conditions[0][0].pointer,
+//!     "/properties/camera/on",
+//! );
+//! ```
+//!
+//! [`Id`]: hazard::Id
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    ops::Not,
+};
+
+use crate::{hazard, risk, Sifis};
+
+impl Sifis {
+    /// Merges another `Sifis` extension into this one.
+    ///
+    /// The hazards of `other` are appended to the current ones, while its risks are only added
+    /// when a risk with the same [`Id`] is not already available.
+    ///
+    /// # Errors
+    ///
+    /// If a risk of `other` has the same [`Id`] of an already available risk but with different
+    /// details, a [`Conflicts`] error containing all the mismatching risks is returned. In this
+    /// case `self` is left untouched.
+    ///
+    /// [`Id`]: hazard::Id
+    pub fn merge(&mut self, other: Sifis) -> Result<(), Conflicts> {
+        let conflicts: Vec<_> = other
+            .risks
+            .iter()
+            .filter_map(|theirs| {
+                self.risks
+                    .iter()
+                    .find(|ours| ours.id() == theirs.id() && *ours != theirs)
+                    .map(|ours| Conflict {
+                        ours: ours.clone(),
+                        theirs: theirs.clone(),
+                    })
+            })
+            .collect();
+
+        if conflicts.is_empty().not() {
+            return Err(Conflicts(conflicts));
+        }
+
+        let Sifis { risks, hazards } = other;
+        for risk in risks {
+            insert_risk(&mut self.risks, risk);
+        }
+        self.hazards.extend(hazards);
+
+        Ok(())
+    }
+
+    /// Moves the JSON pointers of all the hazard conditions from `base` to `new_base`.
+    ///
+    /// Only the pointers that are equal to or descendants of `base` are changed, the others are
+    /// left untouched. See [`JsonPointer::rebase`] for more information.
+    ///
+    /// [`JsonPointer::rebase`]: hazard::JsonPointer::rebase
+    pub fn rebase(&mut self, base: &hazard::JsonPointer, new_base: &hazard::JsonPointer) {
+        self.hazards
+            .iter_mut()
+            .flat_map(|hazard| hazard.conditions.iter_mut().flatten())
+            .for_each(|condition| {
+                if let Some(pointer) = condition.pointer.rebase(base, new_base) {
+                    condition.pointer = pointer;
+                }
+            });
+    }
+}
+
+/// Adds the risk to the set, unless a risk with the same id is already present.
+///
+/// Returns the already available risk with the same id, if any.
+pub(crate) fn insert_risk(
+    risks: &mut Vec<risk::Detail>,
+    risk: risk::Detail,
+) -> Option<&risk::Detail> {
+    if let Some(index) = risks.iter().position(|cur| cur.id() == risk.id()) {
+        Some(&risks[index])
+    } else {
+        risks.push(risk);
+        None
+    }
+}
+
+/// Two risks with the same id but different details.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflict {
+    /// The risk that was already available.
+    pub ours: risk::Detail,
+
+    /// The risk that was going to be merged.
+    pub theirs: risk::Detail,
+}
+
+/// The error returned when merging `Sifis` extensions with conflicting risks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Conflicts(pub Vec<Conflict>);
+
+impl Display for Conflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("conflicting risk details for ids: ")?;
+
+        let mut first = true;
+        for conflict in &self.0 {
+            if first {
+                first = false;
+            } else {
+                f.write_str(", ")?;
+            }
+            write!(f, "{:?}", conflict.ours.id())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for Conflicts {}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        condition::Condition,
+        hazard::{self, Hazard},
+        risk::{EXPLOSION, FIRE_HAZARD, TAKE_PICTURES},
+    };
+
+    use super::*;
+
+    #[test]
+    fn merge_deduplicates_risks() {
+        let mut sifis = Sifis::builder()
+            .fire_hazard(1, |cond| cond)
+            .explosion(2, |cond| cond)
+            .build();
+
+        sifis
+            .merge(
+                Sifis::builder()
+                    .take_pictures(3, |cond| cond)
+                    .fire_hazard(4, |cond| cond)
+                    .build(),
+            )
+            .unwrap();
+
+        assert_eq!(sifis.risks, [FIRE_HAZARD, EXPLOSION, TAKE_PICTURES]);
+        assert_eq!(
+            sifis
+                .hazards
+                .iter()
+                .map(|hazard| hazard.risk)
+                .collect::<Vec<_>>(),
+            [
+                hazard::Risk {
+                    id: hazard::Id::FireHazard,
+                    level: 1,
+                },
+                hazard::Risk {
+                    id: hazard::Id::Explosion,
+                    level: 2,
+                },
+                hazard::Risk {
+                    id: hazard::Id::TakePictures,
+                    level: 3,
+                },
+                hazard::Risk {
+                    id: hazard::Id::FireHazard,
+                    level: 4,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn merge_conflicting_risks() {
+        let mut sifis = Sifis::builder().fire_hazard(1, |cond| cond).build();
+        let original = sifis.clone();
+
+        let mut custom_fire = FIRE_HAZARD;
+        custom_fire.description = Cow::Borrowed("A custom description");
+        let other = Sifis {
+            risks: vec![TAKE_PICTURES, custom_fire.clone()],
+            hazards: Vec::new(),
+        };
+
+        assert_eq!(
+            sifis.merge(other),
+            Err(Conflicts(vec![Conflict {
+                ours: FIRE_HAZARD,
+                theirs: custom_fire,
+            }])),
+        );
+        assert_eq!(sifis, original);
+    }
+
+    #[test]
+    fn rebase_pointers() {
+        let mut sifis = Sifis {
+            risks: vec![FIRE_HAZARD],
+            hazards: vec![Hazard {
+                risk: hazard::Risk {
+                    id: hazard::Id::FireHazard,
+                    level: 1,
+                },
+                conditions: vec![
+                    vec![hazard::Condition {
+                        pointer: "/properties/on".try_into().unwrap(),
+                        condition: Condition::Value(true.into()),
+                    }],
+                    vec![hazard::Condition {
+                        pointer: "/actions/toggle".try_into().unwrap(),
+                        condition: Condition::Value(true.into()),
+                    }],
+                    vec![hazard::Condition {
+                        pointer: "/propertiesX".try_into().unwrap(),
+                        condition: Condition::Value(true.into()),
+                    }],
+                ],
+            }],
+        };
+
+        sifis.rebase(
+            &"/properties".try_into().unwrap(),
+            &"/properties/camera".try_into().unwrap(),
+        );
+
+        let pointers: Vec<_> = sifis.hazards[0]
+            .conditions
+            .iter()
+            .flatten()
+            .map(|condition| &*condition.pointer)
+            .collect();
+        assert_eq!(
+            pointers,
+            ["/properties/camera/on", "/actions/toggle", "/propertiesX"],
+        );
+    }
+
+    #[test]
+    fn rebase_from_root() {
+        let pointer = hazard::JsonPointer::try_from("/properties/on").unwrap();
+        assert_eq!(
+            pointer
+                .rebase(&"".try_into().unwrap(), &"/camera".try_into().unwrap())
+                .as_deref(),
+            Some("/camera/properties/on"),
+        );
+    }
+}
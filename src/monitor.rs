@@ -0,0 +1,441 @@
+//! Continuous monitoring of the risks of multiple Things, using asynchronous streams.
+//!
+//! A [`Monitor`] wraps a [`Stream`] of state updates for a set of Things, and it yields the
+//! transitions of the risks computed from the [`Sifis`] extension of each Thing. It does not
+//! depend on any async runtime.
+//!
+//! The updates that are ready are processed in batches, and the transitions of the same risk of
+//! the same Thing are coalesced until they are consumed: a slow consumer only receives the net
+//! change (i.e.: a level going from 3 to 7 and back to 3 produces no transitions at all).
+//!
+//! This module is only available with the `async` feature.
+//!
+//! ```
+//! use futures::{executor::block_on, stream, StreamExt};
+//! use serde_json::json;
+//! use sifis_td::{
+//!     hazard,
+//!     incremental::RiskChanged,
+//!     monitor::{Monitor, StateUpdate, Transition},
+//!     Sifis,
+//! };
+//!
+//! let lamp = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .build();
+//!
+//! let updates = stream::iter([(
+//!     "lamp",
+//!     StateUpdate::Change {
+//!         pointer: "/properties/brightness".try_into().unwrap(),
+//!         value: json!(50),
+//!     },
+//! )]);
+//!
+//! let transitions: Vec<_> = block_on(Monitor::new(updates, [("lamp", lamp)]).collect());
+//! assert_eq!(
+//!     transitions,
+//!     [Ok(Transition {
+//!         thing: "lamp",
+//!         change: RiskChanged {
+//!             id: hazard::Id::FireHazard,
+//!             old_level: None,
+//!             new_level: Some(3),
+//!         },
+//!     })],
+//! );
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    ops::Not,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use serde_json::Value;
+
+use crate::{
+    hazard::JsonPointer,
+    incremental::{IncrementalEvaluator, PatchError, PatchOperation, RiskChanged},
+    Sifis,
+};
+
+/// The default maximum number of updates processed in a single poll.
+const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// An update of the state of a Thing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateUpdate {
+    /// Replaces the whole state.
+    Replace(Value),
+
+    /// Sets the value behind a JSON pointer.
+    ///
+    /// See [`IncrementalEvaluator::apply_change`] for more information.
+    Change {
+        /// The location of the value.
+        pointer: JsonPointer,
+
+        /// The new value.
+        value: Value,
+    },
+
+    /// Applies a JSON Patch (RFC 6902).
+    Patch(Vec<PatchOperation>),
+}
+
+/// A transition of a risk of a Thing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transition<K> {
+    /// The id of the Thing.
+    pub thing: K,
+
+    /// The change of level of the risk.
+    pub change: RiskChanged,
+}
+
+/// A stream adaptor yielding the risk transitions caused by a stream of state updates.
+///
+/// The inner stream yields pairs of Thing ids and [`StateUpdate`]s. The initial state of each
+/// Thing is an empty object.
+///
+/// The inner stream must be [`Unpin`], use [`Box::pin`] for the streams that are not.
+#[must_use = "streams do nothing unless polled"]
+pub struct Monitor<S, K> {
+    inner: S,
+    things: HashMap<K, IncrementalEvaluator>,
+    pending: VecDeque<Result<Transition<K>, UpdateError<K>>>,
+    batch_size: usize,
+    done: bool,
+}
+
+impl<S, K> Monitor<S, K>
+where
+    S: Stream<Item = (K, StateUpdate)> + Unpin,
+    K: Eq + Hash + Clone,
+{
+    /// Creates a monitor for a set of Things, each one with its `Sifis` extension.
+    pub fn new<I>(inner: S, things: I) -> Self
+    where
+        I: IntoIterator<Item = (K, Sifis)>,
+    {
+        let things = things
+            .into_iter()
+            .map(|(id, sifis)| {
+                let evaluator =
+                    IncrementalEvaluator::new(&sifis, Value::Object(serde_json::Map::new()));
+                (id, evaluator)
+            })
+            .collect();
+
+        Self {
+            inner,
+            things,
+            pending: VecDeque::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            done: false,
+        }
+    }
+
+    /// Sets the maximum number of updates processed in a single poll.
+    ///
+    /// A larger batch coalesces more transitions, a smaller one reduces the latency. The minimum
+    /// is 1.
+    #[inline]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the evaluator of a Thing, which contains its current state and risks.
+    #[inline]
+    pub fn thing(&self, id: &K) -> Option<&IncrementalEvaluator> {
+        self.things.get(id)
+    }
+
+    fn process(&mut self, thing: K, update: StateUpdate) {
+        let Some(evaluator) = self.things.get_mut(&thing) else {
+            self.pending.push_back(Err(UpdateError {
+                thing,
+                kind: UpdateErrorKind::UnknownThing,
+            }));
+            return;
+        };
+
+        let changes = match update {
+            StateUpdate::Replace(state) => Ok(evaluator.replace_state(state)),
+            StateUpdate::Change { pointer, value } => evaluator.apply_change(&pointer, value),
+            StateUpdate::Patch(patch) => evaluator.apply_patch(&patch),
+        };
+
+        let changes = match changes {
+            Ok(changes) => changes,
+            Err(err) => {
+                self.pending.push_back(Err(UpdateError {
+                    thing,
+                    kind: UpdateErrorKind::Patch(err),
+                }));
+                return;
+            }
+        };
+
+        for change in changes {
+            let queued = self.pending.iter_mut().position(|queued| {
+                queued
+                    .as_ref()
+                    .is_ok_and(|queued| queued.thing == thing && queued.change.id == change.id)
+            });
+
+            match queued {
+                Some(index) => {
+                    let Ok(queued) = &mut self.pending[index] else {
+                        unreachable!("only transitions are coalesced");
+                    };
+                    queued.change.new_level = change.new_level;
+                    if queued.change.old_level == queued.change.new_level {
+                        self.pending.remove(index);
+                    }
+                }
+                None => self.pending.push_back(Ok(Transition {
+                    thing: thing.clone(),
+                    change,
+                })),
+            }
+        }
+    }
+}
+
+impl<S, K> Stream for Monitor<S, K>
+where
+    S: Stream<Item = (K, StateUpdate)> + Unpin,
+    K: Eq + Hash + Clone + Unpin,
+{
+    type Item = Result<Transition<K>, UpdateError<K>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let mut budget = this.batch_size;
+        while budget > 0 && this.done.not() {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some((thing, update))) => {
+                    this.process(thing, update);
+                    budget -= 1;
+                }
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(item) = this.pending.pop_front() {
+            return Poll::Ready(Some(item));
+        }
+
+        if this.done {
+            Poll::Ready(None)
+        } else {
+            if budget == 0 {
+                // The inner stream has more ready items, but it did not register the waker.
+                cx.waker().wake_by_ref();
+            }
+            Poll::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Transitions can be coalesced, therefore only the error and the pending items are sure.
+        let upper = if self.done {
+            Some(self.pending.len())
+        } else {
+            None
+        };
+        (0, upper)
+    }
+}
+
+impl<S, K> Debug for Monitor<S, K>
+where
+    K: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Monitor")
+            .field("things", &self.things)
+            .field("pending", &self.pending)
+            .field("batch_size", &self.batch_size)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error yielded when an update cannot be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UpdateError<K> {
+    /// The id of the Thing.
+    pub thing: K,
+
+    /// The reason of the failure.
+    pub kind: UpdateErrorKind,
+}
+
+/// The reason of the failure of an update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateErrorKind {
+    /// The Thing is not monitored.
+    UnknownThing,
+
+    /// The change or the JSON Patch cannot be applied.
+    Patch(PatchError),
+}
+
+impl<K> Display for UpdateError<K>
+where
+    K: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            UpdateErrorKind::UnknownThing => write!(f, "unknown thing {:?}", self.thing),
+            UpdateErrorKind::Patch(err) => {
+                write!(f, "invalid update for thing {:?}: {err}", self.thing)
+            }
+        }
+    }
+}
+
+impl<K> Error for UpdateError<K> where K: Debug {}
+
+#[cfg(test)]
+mod tests {
+    use futures::{channel::mpsc, executor::block_on, stream, StreamExt};
+    use serde_json::json;
+
+    use crate::{hazard, incremental::PatchErrorKind};
+
+    use super::*;
+
+    fn lamp() -> Sifis {
+        Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+            .fire_hazard(7, |cond| cond.when("/properties/brightness").ge(80))
+            .build()
+    }
+
+    fn brightness(value: u32) -> StateUpdate {
+        StateUpdate::Change {
+            pointer: "/properties/brightness".try_into().unwrap(),
+            value: json!(value),
+        }
+    }
+
+    fn fire(
+        thing: &'static str,
+        old_level: Option<u8>,
+        new_level: Option<u8>,
+    ) -> Transition<&'static str> {
+        Transition {
+            thing,
+            change: RiskChanged {
+                id: hazard::Id::FireHazard,
+                old_level,
+                new_level,
+            },
+        }
+    }
+
+    #[test]
+    fn coalesce_ready_updates() {
+        let updates = stream::iter([
+            ("a", brightness(50)),
+            ("b", brightness(90)),
+            ("a", brightness(90)),
+            ("b", brightness(10)),
+            ("c", brightness(10)),
+            (
+                "a",
+                StateUpdate::Replace(json!({ "properties": { "brightness": 60 } })),
+            ),
+        ]);
+
+        let transitions: Vec<_> =
+            block_on(Monitor::new(updates, [("a", lamp()), ("b", lamp())]).collect());
+        assert_eq!(
+            transitions,
+            [
+                Ok(fire("a", None, Some(3))),
+                Err(UpdateError {
+                    thing: "c",
+                    kind: UpdateErrorKind::UnknownThing,
+                }),
+            ],
+        );
+    }
+
+    #[test]
+    fn batch_size() {
+        let updates = stream::iter([
+            ("a", brightness(50)),
+            ("a", brightness(90)),
+            ("a", brightness(10)),
+        ]);
+
+        let transitions: Vec<_> = block_on(
+            Monitor::new(updates, [("a", lamp())])
+                .batch_size(1)
+                .collect(),
+        );
+        assert_eq!(
+            transitions,
+            [
+                Ok(fire("a", None, Some(3))),
+                Ok(fire("a", Some(3), Some(7))),
+                Ok(fire("a", Some(7), None)),
+            ],
+        );
+    }
+
+    #[test]
+    fn channel_updates() {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut monitor = Monitor::new(receiver, [("a", lamp())]);
+
+        block_on(async {
+            sender.unbounded_send(("a", brightness(90))).unwrap();
+            assert_eq!(monitor.next().await, Some(Ok(fire("a", None, Some(7)))));
+
+            let patch = serde_json::from_value(json!([
+                { "op": "remove", "path": "/properties/on" },
+            ]))
+            .unwrap();
+            sender
+                .unbounded_send(("a", StateUpdate::Patch(patch)))
+                .unwrap();
+            sender.unbounded_send(("a", brightness(50))).unwrap();
+
+            let err = monitor.next().await.unwrap().unwrap_err();
+            assert_eq!(
+                err.kind,
+                UpdateErrorKind::Patch(PatchError {
+                    operation: 0,
+                    kind: PatchErrorKind::NotFound,
+                }),
+            );
+            assert_eq!(
+                err.to_string(),
+                "invalid update for thing \"a\": JSON patch operation #0 failed: value not found",
+            );
+            assert_eq!(monitor.next().await, Some(Ok(fire("a", Some(7), Some(3)))));
+
+            sender.close_channel();
+            assert_eq!(monitor.next().await, None);
+        });
+
+        assert_eq!(
+            monitor.thing(&"a").unwrap().state(),
+            &json!({ "properties": { "brightness": 50 } }),
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! User policies deciding which risks are acceptable.
+//!
+//! A [`Policy`] maps risks, identified by their [`hazard::Id`] or their [`hazard::Category`], and
+//! a minimum level to an [`Action`]. The risks resulting from an evaluation, like the ones
+//! returned by [`Sifis::evaluate`], are checked using [`Policy::decide`].
+//!
+//! Policies can be serialized, in order to be stored for each user:
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{
+//!     hazard,
+//!     policy::{Action, Policy},
+//!     Sifis,
+//! };
+//!
+//! let policy: Policy = serde_json::from_value(json!({
+//!     "rules": [
+//!         { "category": "sho:Privacy", "level": 1, "action": "require_confirmation" },
+//!         { "id": "sho:FireHazard", "level": 5, "action": "deny" },
+//!     ],
+//! }))
+//! .unwrap();
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+//!     .fire_hazard(7, |cond| cond.when("/properties/brightness").ge(80))
+//!     .build();
+//!
+//! let risks = sifis.evaluate(&json!({ "properties": { "brightness": 50 } }));
+//! assert_eq!(policy.decide(&risks).action, Action::Allow);
+//!
+//! let risks = sifis.evaluate(&json!({ "properties": { "brightness": 90 } }));
+//! let decision = policy.decide(&risks);
+//! assert_eq!(decision.action, Action::Deny);
+//! assert_eq!(decision.offending[0].risk.id, hazard::Id::FireHazard);
+//! ```
+//!
+//! [`Sifis::evaluate`]: crate::Sifis::evaluate
+
+use serde::{Deserialize, Serialize};
+
+use crate::hazard;
+
+/// A set of rules deciding which risks are acceptable.
+///
+/// When multiple rules match a risk, the strictest action is taken. Risks not matched by any rule
+/// get the [`default`] action.
+///
+/// [`default`]: Policy::default
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Policy {
+    /// The rules of the policy.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub rules: Vec<Rule>,
+
+    /// The action for the risks not matched by any rule.
+    #[serde(default)]
+    pub default: Action,
+}
+
+/// A rule of a [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rule {
+    /// The risks the rule applies to.
+    #[serde(flatten)]
+    pub target: Target,
+
+    /// The minimum level of the risks the rule applies to.
+    pub level: u8,
+
+    /// The action taken for the matching risks.
+    pub action: Action,
+}
+
+/// The risks a [`Rule`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// The risks with a specific id.
+    Id(hazard::Id),
+
+    /// The risks with a specific category.
+    Category(hazard::Category),
+}
+
+/// The action taken for a risk.
+///
+/// Actions are ordered from the most permissive to the strictest.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// The risk is accepted.
+    #[default]
+    Allow,
+
+    /// The risk is accepted only after an explicit confirmation from the user.
+    RequireConfirmation,
+
+    /// The risk is not accepted.
+    Deny,
+}
+
+/// The outcome of the check of a set of risks against a [`Policy`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Decision {
+    /// The strictest action among the risks, or [`Action::Allow`] if there are no risks.
+    pub action: Action,
+
+    /// The risks that are not allowed, in the order they have been checked.
+    pub offending: Vec<Offense>,
+}
+
+/// A risk that is not allowed by a [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Offense {
+    /// The risk.
+    pub risk: hazard::Risk,
+
+    /// The action taken for the risk.
+    pub action: Action,
+}
+
+impl Policy {
+    /// Decides the action to take for a set of risks.
+    ///
+    /// The risks are usually obtained from an evaluation, like [`Sifis::evaluate`] or
+    /// [`CompiledSifis::evaluate`].
+    ///
+    /// [`Sifis::evaluate`]: crate::Sifis::evaluate
+    /// [`CompiledSifis::evaluate`]: crate::compiled::CompiledSifis::evaluate
+    #[must_use]
+    pub fn decide(&self, risks: &[hazard::Risk]) -> Decision {
+        let offending: Vec<_> = risks
+            .iter()
+            .map(|&risk| Offense {
+                risk,
+                action: self.action(risk),
+            })
+            .filter(|offense| offense.action != Action::Allow)
+            .collect();
+
+        let action = offending
+            .iter()
+            .map(|offense| offense.action)
+            .max()
+            .unwrap_or_default();
+
+        Decision { action, offending }
+    }
+
+    /// Returns the action to take for a single risk.
+    #[must_use]
+    pub fn action(&self, risk: hazard::Risk) -> Action {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(risk))
+            .map(|rule| rule.action)
+            .max()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Rule {
+    /// Checks whether the rule applies to a risk.
+    #[inline]
+    #[must_use]
+    pub fn matches(&self, risk: hazard::Risk) -> bool {
+        let target = match self.target {
+            Target::Id(id) => risk.id == id,
+            Target::Category(category) => risk.id.category() == category,
+        };
+
+        target && risk.level >= self.level
+    }
+}
+
+impl Decision {
+    /// Returns `true` if the risks are accepted without confirmation.
+    #[inline]
+    #[must_use]
+    pub fn is_allowed(&self) -> bool {
+        self.action == Action::Allow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn risk(id: hazard::Id, level: u8) -> hazard::Risk {
+        hazard::Risk { id, level }
+    }
+
+    #[test]
+    fn decide() {
+        let policy = Policy {
+            rules: vec![
+                Rule {
+                    target: Target::Category(hazard::Category::Safety),
+                    level: 3,
+                    action: Action::RequireConfirmation,
+                },
+                Rule {
+                    target: Target::Id(hazard::Id::Explosion),
+                    level: 0,
+                    action: Action::Deny,
+                },
+                Rule {
+                    target: Target::Id(hazard::Id::FireHazard),
+                    level: 8,
+                    action: Action::Deny,
+                },
+                Rule {
+                    target: Target::Id(hazard::Id::Burn),
+                    level: 0,
+                    action: Action::Allow,
+                },
+            ],
+            default: Action::Allow,
+        };
+
+        assert!(policy.decide(&[]).is_allowed());
+        assert!(policy
+            .decide(&[
+                risk(hazard::Id::FireHazard, 2),
+                risk(hazard::Id::TakePictures, 9)
+            ])
+            .is_allowed());
+
+        // The strictest rule wins, even when a more specific one allows the risk.
+        assert_eq!(
+            policy.decide(&[
+                risk(hazard::Id::Burn, 5),
+                risk(hazard::Id::TakePictures, 9),
+                risk(hazard::Id::FireHazard, 8),
+            ]),
+            Decision {
+                action: Action::Deny,
+                offending: vec![
+                    Offense {
+                        risk: risk(hazard::Id::Burn, 5),
+                        action: Action::RequireConfirmation,
+                    },
+                    Offense {
+                        risk: risk(hazard::Id::FireHazard, 8),
+                        action: Action::Deny,
+                    },
+                ],
+            },
+        );
+        assert_eq!(policy.action(risk(hazard::Id::Explosion, 0)), Action::Deny);
+
+        let strict = Policy {
+            default: Action::Deny,
+            ..policy
+        };
+        assert_eq!(
+            strict.action(risk(hazard::Id::TakePictures, 1)),
+            Action::Deny
+        );
+    }
+
+    #[test]
+    fn serde() {
+        let policy = Policy {
+            rules: vec![Rule {
+                target: Target::Category(hazard::Category::Financial),
+                level: 4,
+                action: Action::RequireConfirmation,
+            }],
+            default: Action::Deny,
+        };
+        let value = json!({
+            "rules": [
+                { "category": "sho:Financial", "level": 4, "action": "require_confirmation" },
+            ],
+            "default": "deny",
+        });
+
+        assert_eq!(serde_json::to_value(&policy).unwrap(), value);
+        assert_eq!(serde_json::from_value::<Policy>(value).unwrap(), policy);
+        assert_eq!(
+            serde_json::from_value::<Policy>(json!({})).unwrap(),
+            Policy::default()
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Prediction of the risks caused by writing data to a Thing.
+//!
+//! Before changing a property or invoking an action, an application can check which risks would
+//! become active using [`Sifis::predict_write`], without changing the actual state.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::Sifis;
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| cond.when("/properties/temperature").ge(100))
+//!     .fire_hazard(7, |cond| cond.when("/properties/temperature").ge(200))
+//!     .build();
+//!
+//! let prediction = sifis
+//!     .predict_write(
+//!         &json!({ "properties": { "temperature": 150 } }),
+//!         &"/properties/temperature".try_into().unwrap(),
+//!         json!(250),
+//!     )
+//!     .unwrap();
+//!
+//! assert!(prediction.is_riskier());
+//! assert_eq!(
+//!     prediction.changes[0].to_string(),
+//!     "Fire hazard raised from 3 to 7",
+//! );
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    hazard::{self, JsonPointer},
+    incremental::{IncrementalEvaluator, PatchError, RiskChanged},
+    Sifis,
+};
+
+impl Sifis {
+    /// Predicts the risks after setting the value behind a JSON pointer.
+    ///
+    /// The change is applied to a copy of `state`, like [`IncrementalEvaluator::apply_change`]
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be set, see [`IncrementalEvaluator::apply_change`].
+    #[inline]
+    pub fn predict_write(
+        &self,
+        state: &Value,
+        pointer: &JsonPointer,
+        value: Value,
+    ) -> Result<Prediction, PatchError> {
+        self.predict_writes(state, [(pointer, value)])
+    }
+
+    /// Predicts the risks after setting multiple values, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the values cannot be set. The [`operation`] of the error is
+    /// the index of the write.
+    ///
+    /// [`operation`]: PatchError::operation
+    pub fn predict_writes<'a, I>(&self, state: &Value, writes: I) -> Result<Prediction, PatchError>
+    where
+        I: IntoIterator<Item = (&'a JsonPointer, Value)>,
+    {
+        let mut evaluator = IncrementalEvaluator::new(self, state.clone());
+        let before = evaluator.risks();
+
+        for (index, (pointer, value)) in writes.into_iter().enumerate() {
+            evaluator
+                .apply_change(pointer, value)
+                .map_err(|err| PatchError {
+                    operation: index,
+                    ..err
+                })?;
+        }
+
+        let after = evaluator.risks();
+        let changes = RiskChanged::between(&before, &after);
+
+        Ok(Prediction {
+            before,
+            after,
+            changes,
+            state: evaluator.state().clone(),
+        })
+    }
+}
+
+/// The risks before and after a hypothetical change of the state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Prediction {
+    /// The risks triggered by the current state, see [`Sifis::evaluate`].
+    pub before: Vec<hazard::Risk>,
+
+    /// The risks that would be triggered after the change.
+    pub after: Vec<hazard::Risk>,
+
+    /// The risks whose level would change.
+    pub changes: Vec<RiskChanged>,
+
+    /// The state after the change.
+    pub state: Value,
+}
+
+impl Prediction {
+    /// Returns `true` if the level of at least one risk would increase.
+    #[inline]
+    #[must_use]
+    pub fn is_riskier(&self) -> bool {
+        self.changes.iter().any(RiskChanged::is_increase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Not;
+
+    use serde_json::json;
+
+    use crate::incremental::PatchErrorKind;
+
+    use super::*;
+
+    fn oven() -> Sifis {
+        Sifis::builder()
+            .burn(2, |cond| cond.when("/properties/temperature").gt(50))
+            .fire_hazard(7, |cond| {
+                cond.when("/properties/temperature")
+                    .gt(250)
+                    .and("/properties/door/open")
+                    .eq(true)
+            })
+            .build()
+    }
+
+    #[test]
+    fn predict_writes() {
+        let sifis = oven();
+        let state = json!({ "properties": { "temperature": 100 } });
+        let temperature = "/properties/temperature".try_into().unwrap();
+        let door = "/properties/door/open".try_into().unwrap();
+
+        let prediction = sifis
+            .predict_writes(&state, [(&temperature, json!(300)), (&door, json!(true))])
+            .unwrap();
+        assert_eq!(prediction.before, sifis.evaluate(&state));
+        assert_eq!(prediction.after, sifis.evaluate(&prediction.state));
+        assert_eq!(
+            prediction.changes,
+            [RiskChanged {
+                id: hazard::Id::FireHazard,
+                old_level: None,
+                new_level: Some(7),
+            }],
+        );
+        assert_eq!(prediction.changes[0].to_string(), "Fire hazard raised to 7");
+        assert!(prediction.is_riskier());
+
+        let prediction = sifis
+            .predict_write(&state, &temperature, json!(20))
+            .unwrap();
+        assert_eq!(prediction.changes[0].to_string(), "Burn cleared from 2");
+        assert!(prediction.is_riskier().not());
+        assert_eq!(prediction.after, []);
+
+        assert_eq!(
+            sifis.predict_writes(
+                &state,
+                [
+                    (&temperature, json!(20)),
+                    (&"/properties/temperature/x".try_into().unwrap(), json!(1))
+                ],
+            ),
+            Err(PatchError {
+                operation: 1,
+                kind: PatchErrorKind::InvalidTarget,
+            }),
+        );
+    }
+
+    #[test]
+    fn predict_append() {
+        let sifis = Sifis::builder()
+            .burn(4, |cond| cond.when("/properties/trays/1").eq("full"))
+            .build();
+        let state = json!({ "properties": { "trays": ["empty"] } });
+
+        let prediction = sifis
+            .predict_write(
+                &state,
+                &"/properties/trays/-".try_into().unwrap(),
+                json!("full"),
+            )
+            .unwrap();
+        assert_eq!(
+            prediction.state["properties"]["trays"],
+            json!(["empty", "full"])
+        );
+        assert_eq!(prediction.after, sifis.evaluate(&prediction.state));
+        assert_eq!(
+            prediction.changes,
+            [RiskChanged {
+                id: hazard::Id::Burn,
+                old_level: None,
+                new_level: Some(4),
+            }],
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Human-readable reports of the hazards of a Thing.
+//!
+//! The reports have a section for each [`hazard::Category`] with the risks of the Thing, using
+//! their name and description from the Thing or from the catalog in [`risk`]. Each risk has a
+//! table with the levels of its hazards and the conditions triggering them in plain language,
+//! using the titles of the affordances instead of the JSON pointers. The reports can be rendered
+//! as Markdown or HTML.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{render, Sifis};
+//! use wot_td::Thing;
+//!
+//! let thing: Thing<Sifis> = serde_json::from_value(json!({
+//!     "@context": "https://www.w3.org/2022/wot/td/v1.1",
+//!     "title": "Lamp",
+//!     "security": "nosec",
+//!     "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+//!     "properties": {
+//!         "brightness": {
+//!             "title": "Brightness",
+//!             "type": "integer",
+//!             "forms": [{ "href": "/brightness" }],
+//!         },
+//!     },
+//!     "sho:hazards": [{
+//!         "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+//!         "sho:conditions": [[{
+//!             "sho:pointer": "/properties/brightness",
+//!             "sho:condition": { "sho:value": 80, "sho:op": "ge" },
+//!         }]],
+//!     }],
+//! }))
+//! .unwrap();
+//!
+//! assert_eq!(
+//!     render::markdown(&thing),
+//!     "# Lamp\n\
+//!      \n\
+//!      ### Safety\n\
+//!      \n\
+//!      #### Fire hazard\n\
+//!      \n\
+//!      The execution may cause fire\n\
+//!      \n\
+//!      | Level | Conditions |\n\
+//!      | --- | --- |\n\
+//!      | 7 | when Brightness is at least 80 |\n",
+//! );
+//! ```
+
+use std::fmt::Write;
+
+use wot_td::{
+    extend::ExtendableThing,
+    thing::{DataSchema, DataSchemaSubtype},
+    Thing,
+};
+
+use crate::{condition, hazard, risk, Sifis};
+
+/// The order of the sections of the categories in the reports.
+const CATEGORIES: [hazard::Category; 3] = [
+    hazard::Category::Safety,
+    hazard::Category::Privacy,
+    hazard::Category::Financial,
+];
+
+/// Renders the hazards of a Thing as Markdown.
+#[must_use]
+pub fn markdown(thing: &Thing<Sifis>) -> String {
+    let mut out = format!("# {}\n", escape_markdown(&thing.title));
+
+    if thing.other.hazards.is_empty() {
+        out.push_str("\nNo hazards.\n");
+        return out;
+    }
+
+    for section in sections(thing) {
+        write!(out, "\n## {}\n", section.category).unwrap();
+        for risk in section.risks {
+            write!(
+                out,
+                "\n### {}\n\n{}\n\n| Level | Conditions |\n| --- | --- |\n",
+                escape_markdown(&risk.detail.name),
+                escape_markdown(&risk.detail.description),
+            )
+            .unwrap();
+            for (level, conditions) in risk.rows {
+                writeln!(out, "| {level} | {} |", escape_markdown(&conditions)).unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+/// Renders the hazards of a Thing as an HTML fragment.
+#[must_use]
+pub fn html(thing: &Thing<Sifis>) -> String {
+    let mut out = format!("<h1>{}</h1>\n", escape_html(&thing.title));
+
+    if thing.other.hazards.is_empty() {
+        out.push_str("<p>No hazards.</p>\n");
+        return out;
+    }
+
+    for section in sections(thing) {
+        writeln!(out, "<h2>{}</h2>", section.category).unwrap();
+        for risk in section.risks {
+            write!(
+                out,
+                "<h3>{}</h3>\n<p>{}</p>\n<table>\n<thead>\n\
+                 <tr><th>Level</th><th>Conditions</th></tr>\n</thead>\n<tbody>\n",
+                escape_html(&risk.detail.name),
+                escape_html(&risk.detail.description),
+            )
+            .unwrap();
+            for (level, conditions) in risk.rows {
+                writeln!(
+                    out,
+                    "<tr><td>{level}</td><td>{}</td></tr>",
+                    escape_html(&conditions),
+                )
+                .unwrap();
+            }
+            out.push_str("</tbody>\n</table>\n");
+        }
+    }
+
+    out
+}
+
+/// The hazards of a category.
+struct Section {
+    category: hazard::Category,
+    risks: Vec<RiskReport>,
+}
+
+/// The hazards associated with the same risk, with their levels and conditions.
+struct RiskReport {
+    id: hazard::Id,
+    detail: risk::Detail,
+    rows: Vec<(u8, String)>,
+}
+
+/// Groups the hazards by category and by risk, in order of appearance.
+///
+/// The names and the descriptions of the risks are taken from the Thing when available, otherwise
+/// from the catalog. The categories always come from the catalog, because the ones declared by the
+/// Thing could be wrong.
+fn sections(thing: &Thing<Sifis>) -> Vec<Section> {
+    let mut risks: Vec<RiskReport> = Vec::new();
+    for hazard in &thing.other.hazards {
+        let id = hazard.risk.id;
+        let row = (hazard.risk.level, conditions_text(thing, hazard));
+
+        match risks.iter_mut().find(|risk| risk.id == id) {
+            Some(risk) => risk.rows.push(row),
+            None => risks.push(RiskReport {
+                id,
+                detail: thing
+                    .other
+                    .risks
+                    .iter()
+                    .find(|detail| detail.id() == id)
+                    .cloned()
+                    .unwrap_or_else(|| id.detail()),
+                rows: vec![row],
+            }),
+        }
+    }
+
+    CATEGORIES
+        .into_iter()
+        .map(|category| Section {
+            category,
+            risks: risks
+                .extract_if(.., |risk| risk.id.category() == category)
+                .collect(),
+        })
+        .filter(|section| !section.risks.is_empty())
+        .collect()
+}
+
+/// The conditions of a hazard in plain language, i.e. `when Brightness is at least 80`.
+fn conditions_text(thing: &Thing<Sifis>, hazard: &hazard::Hazard) -> String {
+    if hazard.conditions.is_empty() {
+        return "never".to_owned();
+    }
+
+    hazard
+        .conditions
+        .iter()
+        .map(|conditions| {
+            if conditions.is_empty() {
+                return "always".to_owned();
+            }
+
+            let conditions = conditions
+                .iter()
+                .map(|condition| condition_text(thing, condition))
+                .collect::<Vec<_>>()
+                .join(" and ");
+            format!("when {conditions}")
+        })
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+fn condition_text(thing: &Thing<Sifis>, condition: &hazard::Condition) -> String {
+    let (value, op) = match &condition.condition {
+        condition::Condition::Value(value) => (value, "is"),
+        condition::Condition::Expr(condition::Expr { value, op }) => (
+            value,
+            match op {
+                condition::Operation::Lt => "is less than",
+                condition::Operation::Le => "is at most",
+                condition::Operation::Ne => "is not",
+                condition::Operation::Gt => "is greater than",
+                condition::Operation::Ge => "is at least",
+            },
+        ),
+    };
+
+    format!("{} {op} {value}", pointer_label(thing, &condition.pointer))
+}
+
+/// The name of the data behind a JSON pointer, using the titles of the affordances.
+///
+/// The pointers follow the same structure of [`Domains::from_thing`]. Each token is replaced by
+/// the title of the affordance or of the field of the data schema, if any, and the raw pointer is
+/// used when it does not refer to an affordance.
+///
+/// [`Domains::from_thing`]: crate::analysis::Domains::from_thing
+fn pointer_label(thing: &Thing<Sifis>, pointer: &hazard::JsonPointer) -> String {
+    if !pointer.0.is_valid() {
+        return pointer.to_string();
+    }
+
+    let tokens: Vec<_> = pointer
+        .0
+        .tokens()
+        .map(|token| token.decoded().to_owned())
+        .collect();
+    let [kind, name, rest @ ..] = tokens.as_slice() else {
+        return pointer.to_string();
+    };
+
+    let affordance = match kind.as_str() {
+        "properties" => thing
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get(name))
+            .map(|property| (&property.interaction.title, Some(&property.data_schema))),
+        "actions" => thing
+            .actions
+            .as_ref()
+            .and_then(|actions| actions.get(name))
+            .map(|action| (&action.interaction.title, action.input.as_ref())),
+        "events" => thing
+            .events
+            .as_ref()
+            .and_then(|events| events.get(name))
+            .map(|event| (&event.interaction.title, event.data.as_ref())),
+        _ => None,
+    };
+    let Some((title, mut schema)) = affordance else {
+        return pointer.to_string();
+    };
+
+    let mut labels = vec![title
+        .as_ref()
+        .or_else(|| schema.and_then(|schema| schema.title.as_ref()))
+        .unwrap_or(name)
+        .clone()];
+    for token in rest {
+        schema = schema.and_then(|schema| field_schema(schema, token));
+        labels.push(
+            schema
+                .and_then(|schema| schema.title.clone())
+                .unwrap_or_else(|| token.clone()),
+        );
+    }
+
+    labels.join(" › ")
+}
+
+type ThingDataSchema = DataSchema<
+    <Sifis as ExtendableThing>::DataSchema,
+    <Sifis as ExtendableThing>::ArraySchema,
+    <Sifis as ExtendableThing>::ObjectSchema,
+>;
+
+/// The schema of a field of an object or of an element of a tuple-like array.
+fn field_schema<'a>(schema: &'a ThingDataSchema, token: &str) -> Option<&'a ThingDataSchema> {
+    match schema.subtype.as_ref()? {
+        DataSchemaSubtype::Object(object) => object.properties.as_ref()?.get(token),
+        DataSchemaSubtype::Array(array) => {
+            array.items.as_deref()?.get(token.parse::<usize>().ok()?)
+        }
+        _ => None,
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn thing() -> Thing<Sifis> {
+        serde_json::from_value(json!({
+            "@context": "https://www.w3.org/2022/wot/td/v1.1",
+            "title": "Oven <A|B>",
+            "security": "nosec",
+            "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+            "properties": {
+                "temperature": {
+                    "title": "Temperature",
+                    "type": "number",
+                    "forms": [{ "href": "/temperature" }],
+                },
+                "mode": {
+                    "type": "string",
+                    "forms": [{ "href": "/mode" }],
+                },
+                "timer": {
+                    "title": "Timer",
+                    "type": "object",
+                    "properties": {
+                        "minutes": { "title": "Minutes", "type": "integer" },
+                        "seconds": { "type": "integer" },
+                    },
+                    "forms": [{ "href": "/timer" }],
+                },
+            },
+            "actions": {
+                "buy": {
+                    "title": "Buy food",
+                    "input": {
+                        "type": "object",
+                        "properties": { "price": { "title": "Price", "type": "number" } },
+                    },
+                    "forms": [{ "href": "/buy" }],
+                },
+            },
+            "sho:risks": [{
+                "@id": "sho:Burn",
+                "sho:category": "sho:Safety",
+                "sho:name": "Burn",
+                "sho:description": "The oven door | glass is hot",
+            }],
+            "sho:hazards": [
+                {
+                    "sho:risk": { "@id": "sho:Burn", "sho:level": 3 },
+                    "sho:conditions": [
+                        [{
+                            "sho:pointer": "/properties/temperature",
+                            "sho:condition": { "sho:value": 50, "sho:op": "gt" },
+                        }],
+                        [{
+                            "sho:pointer": "/properties/mode",
+                            "sho:condition": "grill",
+                        }],
+                    ],
+                },
+                {
+                    "sho:risk": { "@id": "sho:SpendMoney", "sho:level": 1 },
+                    "sho:conditions": [[
+                        {
+                            "sho:pointer": "/actions/buy/price",
+                            "sho:condition": { "sho:value": 10, "sho:op": "ge" },
+                        },
+                    ]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:Burn", "sho:level": 5 },
+                    "sho:conditions": [[
+                        {
+                            "sho:pointer": "/properties/timer/minutes",
+                            "sho:condition": { "sho:value": 60, "sho:op": "ge" },
+                        },
+                        {
+                            "sho:pointer": "/properties/timer/seconds",
+                            "sho:condition": { "sho:value": 0, "sho:op": "le" },
+                        },
+                    ]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:TakePictures", "sho:level": 2 },
+                },
+                {
+                    "sho:risk": { "@id": "sho:Scald", "sho:level": 4 },
+                    "sho:conditions": [
+                        [{
+                            "sho:pointer": "/properties/steam/on",
+                            "sho:condition": true,
+                        }],
+                        [],
+                    ],
+                },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn render_markdown() {
+        assert_eq!(
+            markdown(&thing()),
+            "# Oven <A\\|B>\n\
+             \n\
+             ## Safety\n\
+             \n\
+             ### Burn\n\
+             \n\
+             The oven door \\| glass is hot\n\
+             \n\
+             | Level | Conditions |\n\
+             | --- | --- |\n\
+             | 3 | when Temperature is greater than 50 or when mode is \"grill\" |\n\
+             | 5 | when Timer › Minutes is at least 60 and Timer › seconds is at most 0 |\n\
+             \n\
+             ### Scald\n\
+             \n\
+             The execution allows usage of devices that may cause scalds\n\
+             \n\
+             | Level | Conditions |\n\
+             | --- | --- |\n\
+             | 4 | when /properties/steam/on is true or always |\n\
+             \n\
+             ## Privacy\n\
+             \n\
+             ### Take pictures\n\
+             \n\
+             The execution authorises the app to use a camera and take photos\n\
+             \n\
+             | Level | Conditions |\n\
+             | --- | --- |\n\
+             | 2 | never |\n\
+             \n\
+             ## Financial\n\
+             \n\
+             ### Spend money\n\
+             \n\
+             The execution authorises the app to use payment information and make a payment \
+             transaction\n\
+             \n\
+             | Level | Conditions |\n\
+             | --- | --- |\n\
+             | 1 | when Buy food › Price is at least 10 |\n",
+        );
+    }
+
+    #[test]
+    fn catalog_categories() {
+        let mut thing = thing();
+        thing.other.risks.push(risk::Detail::from_parts(
+            hazard::Id::TakePictures,
+            hazard::Category::Safety,
+            "The oven has a camera".into(),
+            "Camera".into(),
+        ));
+
+        let sections: Vec<_> = sections(&thing)
+            .into_iter()
+            .map(|section| {
+                let names: Vec<_> = section
+                    .risks
+                    .into_iter()
+                    .map(|risk| risk.detail.name.into_owned())
+                    .collect();
+                (section.category, names)
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                (
+                    hazard::Category::Safety,
+                    vec!["Burn".to_owned(), "Scald".to_owned()]
+                ),
+                (hazard::Category::Privacy, vec!["Camera".to_owned()]),
+                (hazard::Category::Financial, vec!["Spend money".to_owned()]),
+            ],
+        );
+    }
+
+    #[test]
+    fn render_html() {
+        let html = html(&thing());
+        assert!(html.starts_with(
+            "<h1>Oven &lt;A|B&gt;</h1>\n\
+             <h2>Safety</h2>\n\
+             <h3>Burn</h3>\n\
+             <p>The oven door | glass is hot</p>\n\
+             <table>\n\
+             <thead>\n\
+             <tr><th>Level</th><th>Conditions</th></tr>\n\
+             </thead>\n\
+             <tbody>\n\
+             <tr><td>3</td><td>when Temperature is greater than 50 or when mode is \
+             &quot;grill&quot;</td></tr>\n"
+        ));
+        assert!(html.contains("</table>\n<h2>Privacy</h2>\n<h3>Take pictures</h3>\n"));
+        assert!(html.contains("<tr><td>1</td><td>when Buy food › Price is at least 10</td></tr>\n"));
+        assert!(html.ends_with("</tbody>\n</table>\n"));
+
+        let empty = Thing::builder("Empty")
+            .ext(Sifis::default())
+            .build()
+            .unwrap();
+        let empty: Thing<Sifis> =
+            serde_json::from_value(serde_json::to_value(empty).unwrap()).unwrap();
+        assert_eq!(super::html(&empty), "<h1>Empty</h1>\n<p>No hazards.</p>\n");
+        assert_eq!(super::markdown(&empty), "# Empty\n\nNo hazards.\n");
+    }
+}
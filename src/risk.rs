@@ -1,6 +1,6 @@
 //! The complete information for risks.
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +8,7 @@ use crate::hazard;
 
 /// The details of a risk.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Detail {
     #[serde(rename = "@id")]
     id: hazard::Id,
@@ -36,6 +37,21 @@ impl Detail {
     pub fn category(&self) -> hazard::Category {
         self.category
     }
+
+    #[inline]
+    pub(crate) fn from_parts(
+        id: hazard::Id,
+        category: hazard::Category,
+        description: Cow<'static, str>,
+        name: Cow<'static, str>,
+    ) -> Self {
+        Self {
+            id,
+            category,
+            description,
+            name,
+        }
+    }
 }
 
 macro_rules! risk {
@@ -57,6 +73,9 @@ macro_rules! risk {
                 category: $category,
             };
         )+
+
+        /// All the risks of the catalog, sorted by name.
+        pub const ALL: &[Detail] = &[$($risk_name),+];
     };
 }
 
@@ -466,4 +485,11 @@ mod tests {
             description: "The execution allows water usage which may lead to flood",
         },
     );
+
+    #[test]
+    fn catalog() {
+        assert_eq!(ALL.len(), 24);
+        assert!(ALL.windows(2).all(|pair| pair[0].name < pair[1].name));
+        assert!(ALL.iter().all(|detail| detail.id().detail() == *detail));
+    }
 }
@@ -0,0 +1,439 @@
+//! Mapping of the values of a piece of data to the resulting risks.
+//!
+//! A [`RiskMap`] describes how the risks of a Thing change with the value behind a JSON pointer,
+//! while the rest of the state is kept fixed. The values are split in contiguous segments, each
+//! one triggering the same set of risks.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{analysis::Domains, Sifis};
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| {
+//!         cond.when("/properties/brightness")
+//!             .ge(40)
+//!             .and("/properties/brightness")
+//!             .lt(80)
+//!     })
+//!     .fire_hazard(7, |cond| {
+//!         cond.when("/properties/brightness")
+//!             .ge(80)
+//!             .and("/properties/on")
+//!             .eq(true)
+//!     })
+//!     .build();
+//!
+//! let map = sifis.risk_map(
+//!     &"/properties/brightness".try_into().unwrap(),
+//!     &json!({ "properties": { "on": true } }),
+//!     &Domains::default(),
+//! );
+//! assert_eq!(
+//!     map.to_string(),
+//!     "/properties/brightness:\n\
+//!      \x20 (-inf, 40): none\n\
+//!      \x20 [40, 80): Fire hazard 3\n\
+//!      \x20 [80, +inf): Fire hazard 7\n",
+//! );
+//! ```
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Bound, Not},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::Domains,
+    condition::Value,
+    domain::{Branch, Domain, Kind, Range},
+    hazard, insert_max_risk, Sifis,
+};
+
+impl Sifis {
+    /// Computes the risks triggered by each value of the data behind a JSON pointer.
+    ///
+    /// The conditions on the other JSON pointers are evaluated against `state`, see
+    /// [`hazard::Condition::is_satisfied_by`] for more information. The value of `pointer` inside
+    /// `state`, if any, is ignored.
+    ///
+    /// When `domains` contains the domain for `pointer`, only the values inside the domain are
+    /// considered, and the bounds of the segments of an integer domain are integers too. If the
+    /// domain is a finite set of values, like for booleans or enumerations, there is a segment for
+    /// each value. Otherwise, the type of the values is inferred from the conditions.
+    #[must_use]
+    pub fn risk_map(
+        &self,
+        pointer: &hazard::JsonPointer,
+        state: &serde_json::Value,
+        domains: &Domains,
+    ) -> RiskMap {
+        let active: Vec<Active> = self
+            .hazards
+            .iter()
+            .flat_map(|hazard| {
+                hazard.conditions.iter().filter_map(move |conditions| {
+                    let (own, others): (Vec<_>, Vec<_>) = conditions
+                        .iter()
+                        .partition(|condition| condition.pointer == *pointer);
+
+                    if others
+                        .iter()
+                        .all(|condition| condition.is_satisfied_by(state))
+                        .not()
+                    {
+                        return None;
+                    }
+
+                    if own.is_empty() {
+                        return Some((hazard.risk, None));
+                    }
+
+                    let range = Branch::from_conditions(own)?.0.into_values().next()?;
+                    Some((hazard.risk, Some(range)))
+                })
+            })
+            .collect();
+
+        let domain = domains.0.get(pointer);
+        let kind = domain.map(|domain| domain.range.kind).or_else(|| {
+            active
+                .iter()
+                .find_map(|(_, range)| range.as_ref().map(|range| range.kind))
+        });
+
+        let Some(kind) = kind else {
+            return RiskMap {
+                pointer: pointer.clone(),
+                segments: vec![Segment {
+                    lower: Bound::Unbounded,
+                    upper: Bound::Unbounded,
+                    risks: risks_where(&active, |_| false),
+                }],
+            };
+        };
+
+        let values = match domain.and_then(|domain| domain.values.as_ref()) {
+            Some(values) => Some(values.clone()),
+            None if kind == Kind::Bool => Some(
+                [false, true]
+                    .into_iter()
+                    .map(Value::Bool)
+                    .filter(|value| domain.is_none_or(|domain| domain.range.contains(value)))
+                    .collect(),
+            ),
+            None => None,
+        };
+
+        let segments = if let Some(values) = values {
+            values
+                .into_iter()
+                .map(|value| Segment {
+                    risks: risks_where(&active, |range| range.contains(&value)),
+                    lower: Bound::Included(value.clone()),
+                    upper: Bound::Included(value),
+                })
+                .collect()
+        } else {
+            continuous_segments(kind, domain, &active)
+        };
+
+        RiskMap {
+            pointer: pointer.clone(),
+            segments,
+        }
+    }
+}
+
+/// A branch triggered by the state, with the values of the pointer satisfying it.
+///
+/// The range is `None` when the branch does not depend on the pointer.
+type Active = (hazard::Risk, Option<Range>);
+
+/// Collects the risks of the active branches satisfied by the values matching the predicate.
+fn risks_where<F>(active: &[Active], predicate: F) -> Vec<hazard::Risk>
+where
+    F: Fn(&Range) -> bool,
+{
+    active
+        .iter()
+        .filter(|(_, range)| range.as_ref().is_none_or(&predicate))
+        .fold(Vec::new(), |mut risks, (risk, _)| {
+            insert_max_risk(&mut risks, *risk);
+            risks
+        })
+}
+
+/// Splits the values of a dense kind into segments triggering the same risks.
+fn continuous_segments(kind: Kind, domain: Option<&Domain>, active: &[Active]) -> Vec<Segment> {
+    let mut points: Vec<Value> = active
+        .iter()
+        .filter_map(|(_, range)| range.as_ref())
+        .chain(domain.map(|domain| &domain.range))
+        .filter(|range| range.kind == kind)
+        .flat_map(|range| {
+            [&range.lower, &range.upper]
+                .into_iter()
+                .filter_map(|bound| match bound {
+                    Bound::Included(value) | Bound::Excluded(value) => Some(value),
+                    Bound::Unbounded => None,
+                })
+                .chain(&range.excluded)
+        })
+        .cloned()
+        .collect();
+    points.sort_by(|a, b| a.compare(b).unwrap_or(Ordering::Equal));
+    points.dedup_by(|a, b| a.compare(b) == Some(Ordering::Equal));
+
+    // Every range has its bounds on the points, therefore each elementary segment is
+    // either contained in a range or disjoint from it.
+    let mut elementary = Vec::with_capacity(points.len() * 2 + 1);
+    let mut lower = Bound::Unbounded;
+    for point in points {
+        elementary.extend(Range::new(kind, lower, Bound::Excluded(point.clone())));
+        elementary.push(Range::point(point.clone()));
+        lower = Bound::Excluded(point);
+    }
+    elementary.extend(Range::new(kind, lower, Bound::Unbounded));
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for range in elementary {
+        let range = match domain.map(|domain| domain.restrict(&range)) {
+            Some(Some(range)) => range,
+            Some(None) => continue,
+            None => range,
+        };
+
+        let risks = risks_where(active, |other| range.is_subset(other));
+        match segments.last_mut() {
+            Some(last) if last.risks == risks => last.upper = range.upper,
+            _ => segments.push(Segment {
+                lower: range.lower,
+                upper: range.upper,
+                risks,
+            }),
+        }
+    }
+
+    segments
+}
+
+/// The risks triggered by the values of the data behind a JSON pointer.
+///
+/// See [`Sifis::risk_map`] for more information.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskMap {
+    /// The JSON pointer to the data.
+    pub pointer: hazard::JsonPointer,
+
+    /// The segments of values, sorted in ascending order.
+    pub segments: Vec<Segment>,
+}
+
+/// A contiguous set of values triggering the same risks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    /// The lower bound of the values.
+    pub lower: Bound<Value>,
+
+    /// The upper bound of the values.
+    pub upper: Bound<Value>,
+
+    /// The triggered risks, with the highest level for each id.
+    pub risks: Vec<hazard::Risk>,
+}
+
+impl Segment {
+    /// Returns the single value of the segment, if the lower and the upper bounds are the same.
+    #[must_use]
+    pub fn as_value(&self) -> Option<&Value> {
+        match (&self.lower, &self.upper) {
+            (Bound::Included(a), Bound::Included(b)) if a.compare(b) == Some(Ordering::Equal) => {
+                Some(a)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for RiskMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", &*self.pointer)?;
+        for segment in &self.segments {
+            writeln!(f, "  {segment}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(value) = self.as_value() {
+            write!(f, "{value}: ")?;
+        } else {
+            match &self.lower {
+                Bound::Included(value) => write!(f, "[{value}, ")?,
+                Bound::Excluded(value) => write!(f, "({value}, ")?,
+                Bound::Unbounded => f.write_str("(-inf, ")?,
+            }
+
+            match &self.upper {
+                Bound::Included(value) => write!(f, "{value}]: ")?,
+                Bound::Excluded(value) => write!(f, "{value}): ")?,
+                Bound::Unbounded => f.write_str("+inf): ")?,
+            }
+        }
+
+        if self.risks.is_empty() {
+            return f.write_str("none");
+        }
+
+        let mut first = true;
+        for risk in &self.risks {
+            if first {
+                first = false;
+            } else {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} {}", risk.id.risk().name, risk.level)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wot_td::{
+        builder::{IntegerDataSchemaBuilderLike, SpecializableDataSchema},
+        Thing,
+    };
+
+    use super::*;
+
+    fn lamp() -> Sifis {
+        Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+            .fire_hazard(7, |cond| {
+                cond.when("/properties/brightness")
+                    .ge(80)
+                    .and("/properties/on")
+                    .eq(true)
+            })
+            .take_pictures(2, |cond| cond.when("/properties/brightness").ne(50))
+            .build()
+    }
+
+    fn fire(level: u8) -> hazard::Risk {
+        hazard::Risk {
+            id: hazard::Id::FireHazard,
+            level,
+        }
+    }
+
+    #[test]
+    fn numeric_segments() {
+        let map = lamp().risk_map(
+            &"/properties/brightness".try_into().unwrap(),
+            &json!({ "properties": { "on": false } }),
+            &Domains::default(),
+        );
+
+        assert_eq!(
+            map.to_string(),
+            "/properties/brightness:\n  \
+             (-inf, 40): Take pictures 2\n  \
+             [40, 50): Fire hazard 3, Take pictures 2\n  \
+             50: Fire hazard 3\n  \
+             (50, +inf): Fire hazard 3, Take pictures 2\n",
+        );
+    }
+
+    #[test]
+    fn schema_domain() {
+        let thing = Thing::builder("lamp")
+            .ext(lamp())
+            .finish_extend()
+            .property("brightness", |b| {
+                b.ext(())
+                    .ext_interaction(())
+                    .ext_data_schema(())
+                    .finish_extend_data_schema()
+                    .integer()
+                    .minimum(0)
+                    .maximum(100)
+            })
+            .build()
+            .unwrap();
+        let domains = Domains::from_thing(&thing);
+
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| cond.when("/properties/brightness").ge(40))
+            .fire_hazard(7, |cond| cond.when("/properties/brightness").gt(79.5))
+            .build();
+        let map = sifis.risk_map(
+            &"/properties/brightness".try_into().unwrap(),
+            &json!({}),
+            &domains,
+        );
+
+        assert_eq!(
+            map.segments,
+            [
+                Segment {
+                    lower: Bound::Included(0.into()),
+                    upper: Bound::Included(39.into()),
+                    risks: vec![],
+                },
+                Segment {
+                    lower: Bound::Included(40.into()),
+                    upper: Bound::Included(79.into()),
+                    risks: vec![fire(3)],
+                },
+                Segment {
+                    lower: Bound::Included(80.into()),
+                    upper: Bound::Included(100.into()),
+                    risks: vec![fire(7)],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn boolean_values() {
+        let map = lamp().risk_map(
+            &"/properties/on".try_into().unwrap(),
+            &json!({ "properties": { "brightness": 90 } }),
+            &Domains::default(),
+        );
+
+        assert_eq!(
+            map.to_string(),
+            "/properties/on:\n  \
+             false: Fire hazard 3, Take pictures 2\n  \
+             true: Fire hazard 7, Take pictures 2\n",
+        );
+    }
+
+    #[test]
+    fn unconstrained_pointer() {
+        let map = lamp().risk_map(
+            &"/properties/other".try_into().unwrap(),
+            &json!({ "properties": { "brightness": 50 } }),
+            &Domains::default(),
+        );
+
+        assert_eq!(
+            map.segments,
+            [Segment {
+                lower: Bound::Unbounded,
+                upper: Bound::Unbounded,
+                risks: vec![fire(3)],
+            }],
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! JSON Schema and SHACL shapes for the `sho:` vocabulary.
+//!
+//! Both are generated from the Rust types and checked into the crate, in
+//! `schema/sifis-td.schema.json` and `schema/sifis-td.shacl.ttl`, for the implementations in
+//! other languages.
+//!
+//! The JSON Schema describes the `sho:risks` and `sho:hazards` members of a Thing Description,
+//! without restricting the other members. For this reason it can be combined with the W3C schema
+//! for Thing Descriptions using `allOf`:
+//!
+//! ```json
+//! {
+//!     "allOf": [
+//!         { "$ref": "td-json-schema-validation.json" },
+//!         { "$ref": "sifis-td.schema.json" }
+//!     ]
+//! }
+//! ```
+//!
+//! The SHACL shapes validate the RDF graph obtained from a Thing Description using the `sho`
+//! prefix of the context. The nested sets of [`conditions`] are flattened in RDF, therefore the
+//! shapes validate each condition but not how they are combined.
+//!
+//! This module is only available with the `schemars` feature.
+//!
+//! [`conditions`]: crate::hazard::Hazard::conditions
+
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::{condition::Operation, risk, Sifis};
+use schemars::generate::SchemaSettings;
+
+/// The JSON Schema for the `Sifis` extension of a Thing Description.
+#[must_use]
+pub fn json_schema() -> serde_json::Value {
+    let mut schema = SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<Sifis>();
+
+    schema.insert(
+        "title".into(),
+        "SIFIS-Home hazards extension for Thing Descriptions".into(),
+    );
+    schema.insert(
+        "description".into(),
+        "The `sho:risks` and `sho:hazards` members of a Thing Description, using the `sho` \
+         prefix for `https://purl.org/sifis/hazards`."
+            .into(),
+    );
+
+    let mut schema = schema.to_value();
+    clean_descriptions(&mut schema);
+    schema
+}
+
+/// The SHACL shapes, in Turtle format, for the `sho:` vocabulary.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn shacl_shapes() -> String {
+    let ids = risk::ALL
+        .iter()
+        .map(|detail| detail.id().as_str())
+        .collect::<Vec<_>>()
+        .join("\n        ");
+    let categories = risk::ALL
+        .iter()
+        .map(|detail| format!("{:?}", detail.category().as_str()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let operations = [
+        Operation::Lt,
+        Operation::Le,
+        Operation::Ne,
+        Operation::Gt,
+        Operation::Ge,
+    ]
+    .map(|op| format!("{:?}", op.as_str()))
+    .join(" ");
+
+    let mut shapes = String::from(
+        "# SHACL shapes for the SIFIS-Home hazards extension of Thing Descriptions.\n\
+         #\n\
+         # Generated by `sifis_td::schema::shacl_shapes`, do not edit by hand.\n\
+         \n\
+         @prefix sh: <http://www.w3.org/ns/shacl#> .\n\
+         @prefix sho: <https://purl.org/sifis/hazards> .\n\
+         @prefix shapes: <https://purl.org/sifis/hazards/shapes#> .\n\
+         @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n",
+    );
+
+    write!(
+        shapes,
+        r#"
+shapes:RiskDetailShape
+    a sh:NodeShape ;
+    sh:targetObjectsOf sho:risks ;
+    sh:nodeKind sh:IRI ;
+    sh:in (
+        {ids}
+    ) ;
+    sh:property [
+        sh:path sho:category ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:in ( {categories} ) ;
+    ] , [
+        sh:path sho:name ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:datatype xsd:string ;
+    ] , [
+        sh:path sho:description ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:datatype xsd:string ;
+    ] .
+
+shapes:HazardShape
+    a sh:NodeShape ;
+    sh:targetObjectsOf sho:hazards ;
+    sh:property [
+        sh:path sho:risk ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:node shapes:RiskShape ;
+    ] , [
+        sh:path sho:conditions ;
+        sh:node shapes:ConditionShape ;
+    ] .
+
+shapes:RiskShape
+    a sh:NodeShape ;
+    sh:nodeKind sh:IRI ;
+    sh:in (
+        {ids}
+    ) ;
+    sh:property [
+        sh:path sho:level ;
+        sh:minCount 1 ;
+        sh:datatype xsd:integer ;
+        sh:minInclusive 0 ;
+        sh:maxInclusive 255 ;
+    ] .
+
+shapes:ConditionShape
+    a sh:NodeShape ;
+    sh:property [
+        sh:path sho:pointer ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:datatype xsd:string ;
+        sh:pattern "^(/([^~/]|~[01])*)*$" ;
+    ] , [
+        sh:path sho:condition ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:or (
+            [ sh:nodeKind sh:Literal ]
+            [ sh:node shapes:ExprShape ]
+        ) ;
+    ] .
+
+shapes:ExprShape
+    a sh:NodeShape ;
+    sh:property [
+        sh:path sho:value ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:nodeKind sh:Literal ;
+    ] , [
+        sh:path sho:op ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:in ( {operations} ) ;
+    ] .
+"#
+    )
+    .expect("writing to a string never fails");
+
+    shapes
+}
+
+/// Removes the Rust-specific markup from the descriptions taken from the documentation.
+///
+/// The paragraphs with code blocks and link definitions are removed, the other ones are unwrapped
+/// and the intra-doc links are replaced with their text.
+fn clean_descriptions(schema: &mut serde_json::Value) {
+    match schema {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(description) if key == "description" => {
+                        *description = clean_description(description);
+                    }
+                    value => clean_descriptions(value),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(clean_descriptions),
+        _ => {}
+    }
+}
+
+fn clean_description(description: &str) -> String {
+    description
+        .split("\n\n")
+        .filter(|paragraph| {
+            !paragraph.contains("```")
+                && !paragraph
+                    .lines()
+                    .all(|line| line.starts_with('[') && line.contains("]: "))
+        })
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace("[`", "`")
+                .replace("`]", "`")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    const UPDATE_VAR: &str = "SIFIS_TD_UPDATE_SCHEMAS";
+
+    /// Reads a checked-in schema, or overwrites it when `SIFIS_TD_UPDATE_SCHEMAS` is set.
+    fn checked_in(name: &str, generated: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("schema")
+            .join(name);
+        if std::env::var_os(UPDATE_VAR).is_some() {
+            fs::write(&path, generated).unwrap();
+        }
+        fs::read_to_string(&path).unwrap()
+    }
+
+    fn valid_fixtures() -> Vec<Value> {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(4.5)
+                    .and("/properties/on")
+                    .eq(true)
+                    .or(|cond| cond.when("/properties/mode").ne("eco"))
+            })
+            .spend_money(2, |cond| cond.when("/actions/buy/amount").gt(-3))
+            .build();
+
+        vec![
+            json!({}),
+            serde_json::to_value(sifis).unwrap(),
+            json!({
+                "sho:risks": risk::ALL,
+                "sho:hazards": [
+                    {
+                        "sho:risk": { "@id": "sho:Scald", "sho:level": 0 },
+                    },
+                    {
+                        "sho:risk": { "@id": "sho:Burn", "sho:level": 255 },
+                        "sho:conditions": [
+                            [
+                                {
+                                    "sho:pointer": "",
+                                    "sho:condition": { "sho:value": 1, "sho:op": "lt" },
+                                },
+                                {
+                                    "sho:pointer": "/properties/a~1b/~0c",
+                                    "sho:condition": { "sho:value": "x", "sho:op": "le" },
+                                },
+                            ],
+                            [],
+                        ],
+                    },
+                ],
+            }),
+            json!({
+                "@context": [
+                    "https://www.w3.org/2022/wot/td/v1.1",
+                    { "sho": "https://purl.org/sifis/hazards" },
+                ],
+                "title": "Lamp",
+                "security": "nosec",
+                "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+                "properties": {
+                    "brightness": {
+                        "type": "integer",
+                        "forms": [{ "href": "/brightness" }],
+                    },
+                },
+                "sho:risks": [risk::FIRE_HAZARD],
+                "sho:hazards": [{
+                    "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/brightness",
+                        "sho:condition": { "sho:value": 80, "sho:op": "ge" },
+                    }]],
+                }],
+            }),
+        ]
+    }
+
+    fn invalid_fixtures() -> Vec<Value> {
+        let hazard = |risk: Value, condition: Value| {
+            json!({
+                "sho:hazards": [{
+                    "sho:risk": risk,
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/on",
+                        "sho:condition": condition,
+                    }]],
+                }],
+            })
+        };
+        let risk = json!({ "@id": "sho:FireHazard", "sho:level": 3 });
+
+        vec![
+            json!({ "sho:hazards": {} }),
+            json!({ "sho:risks": [{ "@id": "sho:FireHazard" }] }),
+            json!({ "sho:hazards": [{ "sho:conditions": [] }] }),
+            hazard(json!({ "@id": "sho:Unknown", "sho:level": 3 }), json!(true)),
+            hazard(
+                json!({ "@id": "sho:FireHazard", "sho:level": 256 }),
+                json!(true),
+            ),
+            hazard(risk.clone(), json!({ "sho:value": 3, "sho:op": "eq" })),
+            hazard(risk.clone(), json!({ "sho:value": [3], "sho:op": "ge" })),
+            hazard(risk, json!(null)),
+        ]
+    }
+
+    #[test]
+    fn json_schema_is_up_to_date() {
+        let generated = json_schema();
+        let checked_in = checked_in(
+            "sifis-td.schema.json",
+            &format!("{}\n", serde_json::to_string_pretty(&generated).unwrap()),
+        );
+
+        assert!(
+            serde_json::from_str::<Value>(&checked_in).unwrap() == generated,
+            "schema/sifis-td.schema.json is outdated, run the tests with {UPDATE_VAR}=1",
+        );
+    }
+
+    #[test]
+    fn shacl_shapes_are_up_to_date() {
+        let generated = shacl_shapes();
+        assert!(
+            checked_in("sifis-td.shacl.ttl", &generated) == generated,
+            "schema/sifis-td.shacl.ttl is outdated, run the tests with {UPDATE_VAR}=1",
+        );
+    }
+
+    #[test]
+    fn json_schema_validates_fixtures() {
+        let schema = serde_json::from_str(include_str!("../schema/sifis-td.schema.json")).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        for fixture in valid_fixtures() {
+            assert!(serde_json::from_value::<Sifis>(fixture.clone()).is_ok());
+            let errors: Vec<_> = validator
+                .iter_errors(&fixture)
+                .map(|err| err.to_string())
+                .collect();
+            assert!(errors.is_empty(), "{fixture}: {errors:?}");
+        }
+
+        for fixture in invalid_fixtures() {
+            assert!(serde_json::from_value::<Sifis>(fixture.clone()).is_err());
+            assert!(!validator.is_valid(&fixture), "{fixture}");
+        }
+
+        // Malformed pointers are accepted by the deserializer, but they are never satisfied.
+        let fixture = json!({
+            "sho:hazards": [{
+                "sho:risk": { "@id": "sho:FireHazard", "sho:level": 3 },
+                "sho:conditions": [[{
+                    "sho:pointer": "properties/on",
+                    "sho:condition": true,
+                }]],
+            }],
+        });
+        assert!(!validator.is_valid(&fixture));
+    }
+
+    /// Checks that all the terms of the fixtures are constrained by the shapes.
+    #[test]
+    fn shacl_shapes_cover_fixtures() {
+        fn collect_terms(value: &Value, terms: &mut BTreeSet<String>) {
+            match value {
+                Value::Object(map) => {
+                    for (key, value) in map {
+                        if let Some(term) = key.strip_prefix("sho:") {
+                            terms.insert(format!(" sho:{term} ;"));
+                        }
+                        match (key.as_str(), value) {
+                            ("@id", Value::String(id)) => {
+                                terms.insert(format!("        {id}\n"));
+                            }
+                            ("sho:category" | "sho:op", Value::String(value)) => {
+                                terms.insert(format!("{value:?}"));
+                            }
+                            _ => collect_terms(value, terms),
+                        }
+                    }
+                }
+                Value::Array(values) => values.iter().for_each(|value| collect_terms(value, terms)),
+                _ => {}
+            }
+        }
+
+        let shapes = include_str!("../schema/sifis-td.shacl.ttl");
+        let mut terms = BTreeSet::new();
+        for fixture in valid_fixtures() {
+            collect_terms(&fixture, &mut terms);
+        }
+        for op in ["lt", "le", "ne", "gt", "ge"] {
+            terms.insert(format!("{op:?}"));
+        }
+
+        let missing: Vec<_> = terms
+            .iter()
+            .filter(|term| !shapes.contains(term.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "{missing:?}");
+
+        // The ids are listed both for the details of the risks and for the hazards.
+        for detail in risk::ALL {
+            let id = detail.id().as_str();
+            assert_eq!(shapes.matches(&format!("        {id}\n")).count(), 2);
+        }
+    }
+}
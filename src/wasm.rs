@@ -0,0 +1,322 @@
+//! WebAssembly bindings, to parse, validate and evaluate the hazards from JavaScript.
+//!
+//! The JSON documents are passed as strings, and the results are returned as arrays of objects
+//! with read-only properties:
+//!
+//! ```js
+//! import { Sifis, riskCatalog } from "sifis_td";
+//!
+//! const sifis = new Sifis(tdJson);
+//! for (const issue of sifis.validate()) {
+//!     console.warn(issue);
+//! }
+//! for (const risk of sifis.evaluate('{"properties": {"brightness": 90}}')) {
+//!     console.log(`${risk.name} (${risk.category}): ${risk.level}`);
+//! }
+//! ```
+//!
+//! The module can be built with `cargo rustc --lib --release --target wasm32-unknown-unknown
+//! --features wasm --crate-type cdylib`, followed by `wasm-bindgen`.
+//!
+//! This module is only available with the `wasm` feature.
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use wot_td::Thing;
+
+use crate::{analysis::Domains, compiled::CompiledSifis, risk, Sifis};
+
+/// The hazards of a Thing Description, exported as `Sifis`.
+#[wasm_bindgen(js_name = Sifis)]
+#[derive(Debug, Clone)]
+pub struct WasmSifis {
+    compiled: CompiledSifis,
+    issues: Vec<String>,
+}
+
+#[wasm_bindgen(js_class = Sifis)]
+impl WasmSifis {
+    /// Parses a Thing Description, or an object with its `sho:risks` and `sho:hazards` only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid, or if it does not describe valid hazards.
+    #[wasm_bindgen(constructor)]
+    pub fn new(json: &str) -> Result<WasmSifis, WasmError> {
+        let value: Value = serde_json::from_str(json).map_err(WasmError::from)?;
+        let sifis: Sifis = serde_json::from_value(value.clone()).map_err(WasmError::from)?;
+
+        // The data types of the affordances are only known for complete Thing Descriptions.
+        let domains = serde_json::from_value::<Thing<Sifis>>(value)
+            .map(|thing| Domains::from_thing(&thing))
+            .unwrap_or_default();
+        let issues = sifis
+            .analyze_with(&domains)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Self {
+            compiled: sifis.compile(),
+            issues,
+        })
+    }
+
+    /// Returns the issues of the conditions of the hazards, see [`Sifis::analyze_with`].
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        self.issues.clone()
+    }
+
+    /// Returns the risks triggered by a state of the Thing, given as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state is not valid JSON.
+    pub fn evaluate(&self, state: &str) -> Result<Vec<WasmRisk>, WasmError> {
+        let state: Value = serde_json::from_str(state).map_err(WasmError::from)?;
+
+        Ok(self
+            .compiled
+            .evaluate(&state)
+            .into_iter()
+            .map(|risk| WasmRisk {
+                detail: WasmRiskDetail::from(&risk.id.detail()),
+                level: risk.level,
+            })
+            .collect())
+    }
+}
+
+/// An active risk, exported as `Risk`.
+#[wasm_bindgen(js_name = Risk)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmRisk {
+    detail: WasmRiskDetail,
+    level: u8,
+}
+
+#[wasm_bindgen(js_class = Risk)]
+impl WasmRisk {
+    /// The id of the risk, i.e. `sho:FireHazard`.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn id(&self) -> String {
+        self.detail.id.clone()
+    }
+
+    /// The name of the risk.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.detail.name.clone()
+    }
+
+    /// The category of the risk, i.e. `Safety`.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn category(&self) -> String {
+        self.detail.category.clone()
+    }
+
+    /// The level of the risk.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+}
+
+/// A risk of the catalog, exported as `RiskDetail`.
+#[wasm_bindgen(js_name = RiskDetail)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmRiskDetail {
+    id: String,
+    name: String,
+    description: String,
+    category: String,
+}
+
+#[wasm_bindgen(js_class = RiskDetail)]
+impl WasmRiskDetail {
+    /// The id of the risk, i.e. `sho:FireHazard`.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// The name of the risk.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The description of the risk.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    /// The category of the risk, i.e. `Safety`.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn category(&self) -> String {
+        self.category.clone()
+    }
+}
+
+impl From<&risk::Detail> for WasmRiskDetail {
+    fn from(detail: &risk::Detail) -> Self {
+        Self {
+            id: detail.id().as_str().to_owned(),
+            name: detail.name.clone().into_owned(),
+            description: detail.description.clone().into_owned(),
+            category: detail.category().to_string(),
+        }
+    }
+}
+
+/// Returns all the risks of the catalog, see [`risk::ALL`].
+#[wasm_bindgen(js_name = riskCatalog)]
+#[must_use]
+pub fn risk_catalog() -> Vec<WasmRiskDetail> {
+    risk::ALL.iter().map(WasmRiskDetail::from).collect()
+}
+
+/// An error parsing JSON, exported as `SifisError`.
+#[wasm_bindgen(js_name = SifisError)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmError {
+    message: String,
+    line: usize,
+    column: usize,
+}
+
+#[wasm_bindgen(js_class = SifisError)]
+impl WasmError {
+    /// The description of the error.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// The line of the error in the JSON, starting from 1, or 0 if unknown.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the error in the JSON, starting from 1, or 0 if unknown.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl From<serde_json::Error> for WasmError {
+    fn from(err: serde_json::Error) -> Self {
+        Self {
+            message: err.to_string(),
+            line: err.line(),
+            column: err.column(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn evaluate() {
+        let td = json!({
+            "@context": "https://www.w3.org/2022/wot/td/v1.1",
+            "title": "Lamp",
+            "security": "nosec",
+            "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+            "properties": {
+                "brightness": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 100,
+                    "forms": [{ "href": "/brightness" }],
+                },
+            },
+            "sho:hazards": [
+                {
+                    "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/brightness",
+                        "sho:condition": { "sho:value": 80, "sho:op": "ge" },
+                    }]],
+                },
+                {
+                    "sho:risk": { "@id": "sho:Burn", "sho:level": 2 },
+                    "sho:conditions": [[{
+                        "sho:pointer": "/properties/brightness",
+                        "sho:condition": { "sho:value": 120, "sho:op": "ge" },
+                    }]],
+                },
+            ],
+        });
+
+        let sifis = WasmSifis::new(&td.to_string()).unwrap();
+        assert_eq!(
+            sifis.validate(),
+            [
+                "hazard #1, branch #0: the conditions can never be satisfied",
+                "hazard #1: the hazard is never triggered",
+            ],
+        );
+
+        let risks = sifis
+            .evaluate(r#"{"properties": {"brightness": 90}}"#)
+            .unwrap();
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].id(), "sho:FireHazard");
+        assert_eq!(risks[0].name(), "Fire hazard");
+        assert_eq!(risks[0].category(), "Safety");
+        assert_eq!(risks[0].level(), 7);
+
+        // The domains of the properties are unknown without the rest of the Thing Description.
+        let hazards = json!({ "sho:hazards": td["sho:hazards"] });
+        assert_eq!(
+            WasmSifis::new(&hazards.to_string()).unwrap().validate(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = WasmSifis::new("{\n  \"sho:hazards\": [").unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 18));
+        assert!(err.message().starts_with("EOF while parsing"));
+
+        assert!(WasmSifis::new(r#"{"sho:hazards": 3}"#).is_err());
+
+        let sifis = WasmSifis::new("{}").unwrap();
+        assert!(sifis.evaluate("[").is_err());
+        assert!(sifis.evaluate("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn catalog() {
+        let catalog = risk_catalog();
+        assert_eq!(catalog.len(), risk::ALL.len());
+        assert_eq!(catalog[0].id(), "sho:AirPoisoning");
+        assert_eq!(catalog[0].name(), "Air poisoning");
+        assert_eq!(catalog[0].category(), "Safety");
+        assert_eq!(
+            catalog[0].description(),
+            "The execution may release toxic gases"
+        );
+    }
+}
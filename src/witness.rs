@@ -0,0 +1,381 @@
+//! Generation of example states triggering the hazards.
+//!
+//! A [`Witness`] is a concrete state of a Thing that satisfies one of the `AND` sets of
+//! conditions of a [`Hazard`], together with a _near miss_: a similar state that does not trigger
+//! the hazard. They are useful to write tests and documentation, or to show to the users when a
+//! hazard occurs.
+//!
+//! ```
+//! use serde_json::json;
+//! use sifis_td::{analysis::Domains, Sifis};
+//!
+//! let sifis = Sifis::builder()
+//!     .fire_hazard(3, |cond| {
+//!         cond.when("/properties/brightness")
+//!             .gt(80)
+//!             .and("/properties/on")
+//!             .eq(true)
+//!     })
+//!     .build();
+//!
+//! let witnesses = sifis.hazards[0].witnesses(&Domains::default());
+//! assert_eq!(
+//!     witnesses[0].state,
+//!     json!({ "properties": { "brightness": 81, "on": true } }),
+//! );
+//! assert_eq!(
+//!     witnesses[0].near_miss,
+//!     Some(json!({ "properties": { "brightness": 80, "on": true } })),
+//! );
+//! ```
+
+use std::ops::{Bound, Not};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::Domains,
+    condition::Value,
+    domain::{self, Branch, Domain, Kind, Range},
+    hazard::Hazard,
+};
+
+impl Hazard {
+    /// Generates an example state for each `AND` set of [`conditions`].
+    ///
+    /// The states only contain the data referred by the conditions of the set, using the
+    /// simplest values satisfying them (i.e.: the bound of a `>=` condition, or the next integer
+    /// for a `>` condition). The values are taken from `domains`, when available.
+    ///
+    /// The sets of conditions that cannot be satisfied are skipped, therefore
+    /// [`Witness::branch`] must be used to identify the set.
+    ///
+    /// [`conditions`]: Hazard::conditions
+    #[must_use]
+    pub fn witnesses(&self, domains: &Domains) -> Vec<Witness> {
+        self.conditions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, conditions)| {
+                let branch = domains.restrict(Branch::from_conditions(conditions)?)?;
+
+                let mut state = serde_json::Value::Object(serde_json::Map::new());
+                for (pointer, range) in &branch.0 {
+                    let domain = domains.0.get(pointer);
+                    let value = inside_candidates(range, domain).into_iter().find(|value| {
+                        range.contains(value) && domain.is_none_or(|d| d.allows(value))
+                    })?;
+                    pointer.0.assign(&mut state, value).ok()?;
+                }
+
+                // Overlapping pointers (i.e.: `/a` and `/a/b`) cannot be satisfied together.
+                if conditions
+                    .iter()
+                    .all(|condition| condition.is_satisfied_by(&state))
+                    .not()
+                {
+                    return None;
+                }
+
+                let near_miss = branch.0.iter().find_map(|(pointer, range)| {
+                    let domain = domains.0.get(pointer);
+                    outside_candidates(range, domain)
+                        .into_iter()
+                        .filter(|value| {
+                            range.contains(value).not() && domain.is_none_or(|d| d.allows(value))
+                        })
+                        .find_map(|value| {
+                            let mut state = state.clone();
+                            pointer.0.assign(&mut state, value).ok()?;
+                            self.is_triggered_by(&state).not().then_some(state)
+                        })
+                });
+
+                Some(Witness {
+                    branch: index,
+                    state,
+                    near_miss,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An example state triggering a hazard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Witness {
+    /// The index of the `AND` set of [`Hazard::conditions`] satisfied by the state.
+    pub branch: usize,
+
+    /// The state of the Thing, with the same structure of the Thing Description.
+    pub state: serde_json::Value,
+
+    /// A state that differs from [`state`] by a single value and does not trigger the hazard.
+    ///
+    /// It is `None` when such a state cannot be found, like for a set of conditions that is
+    /// always satisfied.
+    ///
+    /// [`state`]: Witness::state
+    pub near_miss: Option<serde_json::Value>,
+}
+
+/// Creates some values that are likely to be inside the range, from the simplest to the most
+/// complex.
+fn inside_candidates(range: &Range, domain: Option<&Domain>) -> Vec<Value> {
+    let mut candidates: Vec<Value> = domain
+        .and_then(|domain| domain.values.clone())
+        .unwrap_or_default();
+
+    for bound in [&range.lower, &range.upper] {
+        if let Bound::Included(value) = bound {
+            candidates.push(value.clone());
+        }
+    }
+
+    match range.kind {
+        Kind::Bool => candidates.extend([Value::Bool(false), Value::Bool(true)]),
+        Kind::Number => {
+            // Excluded values can hide the integers near the anchors, but not all of them.
+            let steps = range.excluded.len() + 1;
+            let anchors: Vec<f64> = bound_values(range)
+                .map(domain::as_f64)
+                .chain([0.])
+                .collect();
+
+            for &anchor in &anchors {
+                for step in 0..=steps {
+                    #[allow(clippy::cast_precision_loss)]
+                    let step = step as f64;
+                    candidates.extend(domain::value_from_f64(anchor.floor() - step));
+                    candidates.extend(domain::value_from_f64(anchor.ceil() + step));
+                }
+            }
+
+            let points: Vec<f64> = finite(&range.lower)
+                .into_iter()
+                .chain(&range.excluded)
+                .chain(finite(&range.upper))
+                .map(domain::as_f64)
+                .collect();
+            candidates.extend(
+                points
+                    .windows(2)
+                    .filter_map(|pair| domain::value_from_f64(pair[0].midpoint(pair[1]))),
+            );
+        }
+        Kind::String => {
+            candidates.push(Value::from(""));
+            let bases = finite(&range.lower).into_iter().chain(&range.excluded);
+            for base in bases {
+                if let Value::String(base) = base {
+                    candidates.extend(
+                        ["a", "z", "0"].map(|suffix| Value::from(format!("{base}{suffix}"))),
+                    );
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Creates some values that are likely to be outside the range, but close to its bounds.
+fn outside_candidates(range: &Range, domain: Option<&Domain>) -> Vec<Value> {
+    let mut candidates = Vec::new();
+
+    match range.kind {
+        Kind::Bool => candidates.extend([Value::Bool(false), Value::Bool(true)]),
+        Kind::Number => {
+            if let Some(value) = finite(&range.lower) {
+                candidates.push(value.clone());
+                candidates.extend(domain::value_from_f64(domain::as_f64(value) - 1.));
+            }
+            if let Some(value) = finite(&range.upper) {
+                candidates.push(value.clone());
+                candidates.extend(domain::value_from_f64(domain::as_f64(value) + 1.));
+            }
+        }
+        Kind::String => {
+            if let Some(value) = finite(&range.lower) {
+                candidates.push(value.clone());
+                candidates.push(Value::from(""));
+            }
+            if let Some(Value::String(value)) = finite(&range.upper) {
+                candidates.push(Value::String(value.clone()));
+                candidates.push(Value::from(format!("{value}a")));
+            }
+        }
+    }
+
+    candidates.extend(range.excluded.iter().cloned());
+    if let Some(domain) = domain {
+        candidates.extend(bound_values(&domain.range).cloned());
+        candidates.extend(domain.values.iter().flatten().cloned());
+    }
+
+    candidates
+}
+
+/// The value of a bound, if it is finite.
+fn finite(bound: &Bound<Value>) -> Option<&Value> {
+    match bound {
+        Bound::Included(value) | Bound::Excluded(value) => Some(value),
+        Bound::Unbounded => None,
+    }
+}
+
+/// The values of the finite bounds of a range, lower first.
+fn bound_values(range: &Range) -> impl Iterator<Item = &Value> {
+    finite(&range.lower).into_iter().chain(finite(&range.upper))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wot_td::{
+        builder::{NumberDataSchemaBuilderLike, SpecializableDataSchema},
+        Thing,
+    };
+
+    use crate::Sifis;
+
+    use super::*;
+
+    fn witnesses(sifis: &Sifis, domains: &Domains) -> Vec<Witness> {
+        let witnesses = sifis.hazards[0].witnesses(domains);
+        for witness in &witnesses {
+            assert!(sifis.hazards[0].conditions[witness.branch]
+                .iter()
+                .all(|condition| condition.is_satisfied_by(&witness.state)));
+            if let Some(near_miss) = &witness.near_miss {
+                assert!(sifis.hazards[0].is_triggered_by(near_miss).not());
+            }
+        }
+        witnesses
+    }
+
+    #[test]
+    fn branches() {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/a")
+                    .lt(3)
+                    .and("/properties/a")
+                    .gt(5)
+                    .or(|cond| {
+                        cond.when("/properties/a")
+                            .gt(2.5)
+                            .and("/properties/a")
+                            .lt(2.9)
+                    })
+                    .or(|cond| {
+                        cond.when("/properties/mode")
+                            .ne("")
+                            .and("/actions/toggle")
+                            .eq(false)
+                    })
+                    .or(|cond| {
+                        cond.when("/properties/a")
+                            .ge(0)
+                            .and("/properties/a")
+                            .le(2)
+                            .and("/properties/a")
+                            .ne(0)
+                            .and("/properties/a")
+                            .ne(1)
+                    })
+            })
+            .build();
+
+        assert_eq!(
+            witnesses(&sifis, &Domains::default()),
+            [
+                Witness {
+                    branch: 1,
+                    state: json!({ "properties": { "a": 2.7 } }),
+                    near_miss: Some(json!({ "properties": { "a": 2.5 } })),
+                },
+                Witness {
+                    branch: 2,
+                    state: json!({
+                        "actions": { "toggle": false },
+                        "properties": { "mode": "a" },
+                    }),
+                    near_miss: Some(json!({
+                        "actions": { "toggle": true },
+                        "properties": { "mode": "a" },
+                    })),
+                },
+                Witness {
+                    branch: 3,
+                    state: json!({ "properties": { "a": 2 } }),
+                    near_miss: Some(json!({ "properties": { "a": 0 } })),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn schema_domain() {
+        let thing = Thing::builder("lamp")
+            .ext(Sifis::default())
+            .finish_extend()
+            .property("level", |b| {
+                b.ext(())
+                    .ext_interaction(())
+                    .ext_data_schema(())
+                    .finish_extend_data_schema()
+                    .number()
+                    .minimum(0.)
+                    .maximum(1.)
+            })
+            .build()
+            .unwrap();
+        let domains = Domains::from_thing(&thing);
+
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/level")
+                    .ge(0.5)
+                    .or(|cond| cond.when("/properties/level").gt(2))
+            })
+            .build();
+
+        assert_eq!(
+            witnesses(&sifis, &domains),
+            [Witness {
+                branch: 0,
+                state: json!({ "properties": { "level": 0.5 } }),
+                near_miss: Some(json!({ "properties": { "level": 0 } })),
+            }],
+        );
+        assert_eq!(witnesses(&sifis, &Domains::default()).len(), 2);
+    }
+
+    #[test]
+    fn always_triggered() {
+        let sifis = Sifis::builder()
+            .fire_hazard(3, |cond| {
+                cond.when("/properties/a")
+                    .ge(3)
+                    .or(|cond| cond.when("/properties/a").lt(3))
+            })
+            .build();
+
+        assert_eq!(
+            witnesses(&sifis, &Domains::default()),
+            [
+                Witness {
+                    branch: 0,
+                    state: json!({ "properties": { "a": 3 } }),
+                    near_miss: None,
+                },
+                Witness {
+                    branch: 1,
+                    state: json!({ "properties": { "a": 2 } }),
+                    near_miss: None,
+                },
+            ],
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Tests of the `sifis-td` command-line tool, run as a separate process to check its exit codes.
+
+#![cfg(feature = "cli")]
+
+use std::{fs, path::PathBuf, process::Command};
+
+use serde_json::{json, Value};
+
+fn lamp() -> Value {
+    json!({
+        "@context": "https://www.w3.org/2022/wot/td/v1.1",
+        "title": "Lamp",
+        "security": "nosec",
+        "securityDefinitions": { "nosec": { "scheme": "nosec" } },
+        "properties": {
+            "brightness": { "type": "integer", "forms": [{ "href": "/brightness" }] },
+        },
+        "sho:risks": [{
+            "@id": "sho:FireHazard",
+            "sho:category": "sho:Safety",
+            "sho:description": "The execution may cause fire",
+            "sho:name": "Fire hazard",
+        }],
+        "sho:hazards": [{
+            "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+            "sho:conditions": [[{
+                "sho:pointer": "/properties/brightness",
+                "sho:condition": { "sho:value": 80, "sho:op": "ge" },
+            }]],
+        }],
+    })
+}
+
+/// A temporary directory containing the input files of a test.
+struct Inputs(PathBuf);
+
+impl Inputs {
+    fn new(test: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("sifis-td-cli-{}-{test}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    fn write(&self, name: &str, value: &Value) -> &Self {
+        fs::write(self.0.join(name), value.to_string()).unwrap();
+        self
+    }
+
+    /// Runs the tool inside the directory, returning its exit code and its output.
+    fn run(&self, args: &[&str]) -> (i32, String) {
+        let output = Command::new(env!("CARGO_BIN_EXE_sifis-td"))
+            .args(args)
+            .current_dir(&self.0)
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        (output.status.code().unwrap(), stdout + &stderr)
+    }
+}
+
+impl Drop for Inputs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn lint() {
+    let mut unused = lamp();
+    unused["sho:risks"].as_array_mut().unwrap().push(json!({
+        "@id": "sho:TakePictures",
+        "sho:category": "sho:Privacy",
+        "sho:description": "The execution may take pictures",
+        "sho:name": "Take pictures",
+    }));
+    let mut undeclared = lamp();
+    undeclared["sho:risks"] = json!([]);
+
+    let inputs = Inputs::new("lint");
+    inputs
+        .write("lamp.json", &lamp())
+        .write("unused.json", &unused)
+        .write("undeclared.json", &undeclared);
+
+    assert_eq!(
+        inputs.run(&["lint", "lamp.json"]),
+        (0, "lamp.json: 0 error(s), 0 warning(s)\n".to_owned()),
+    );
+
+    let output = "warning: risk Take pictures is never used\n\
+                  unused.json: 0 error(s), 1 warning(s)\n";
+    assert_eq!(inputs.run(&["lint", "unused.json"]), (0, output.to_owned()));
+    assert_eq!(
+        inputs.run(&["lint", "--deny-warnings", "unused.json"]),
+        (1, output.to_owned()),
+    );
+
+    assert_eq!(
+        inputs.run(&["lint", "undeclared.json"]),
+        (
+            1,
+            "error: hazard #0: risk Fire hazard is not declared in sho:risks\n\
+             undeclared.json: 1 error(s), 0 warning(s)\n"
+                .to_owned(),
+        ),
+    );
+}
+
+#[test]
+fn eval() {
+    let inputs = Inputs::new("eval");
+    inputs
+        .write("lamp.json", &lamp())
+        .write("on.json", &json!({ "properties": { "brightness": 90 } }))
+        .write("off.json", &json!({ "properties": { "brightness": 0 } }));
+
+    assert_eq!(
+        inputs.run(&["eval", "lamp.json", "on.json"]),
+        (0, "Fire hazard: 7\n".to_owned()),
+    );
+    assert_eq!(
+        inputs.run(&["eval", "lamp.json", "off.json"]),
+        (0, "no active risks\n".to_owned()),
+    );
+
+    let (code, output) = inputs.run(&["eval", "--json", "lamp.json", "on.json"]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        serde_json::from_str::<Value>(&output).unwrap(),
+        json!([{ "@id": "sho:FireHazard", "sho:level": 7 }]),
+    );
+}
+
+#[test]
+fn diff() {
+    let mut new = lamp();
+    new["sho:hazards"][0]["sho:risk"]["sho:level"] = json!(8);
+
+    let inputs = Inputs::new("diff");
+    inputs.write("old.json", &lamp()).write("new.json", &new);
+
+    assert_eq!(
+        inputs.run(&["diff", "--exit-code", "old.json", "old.json"]),
+        (0, "no differences\n".to_owned()),
+    );
+
+    let output = "~ Fire hazard\n    \
+                  maximum level increased from 7 to 8\n    \
+                  + level 8\n    \
+                  - level 7\n";
+    assert_eq!(
+        inputs.run(&["diff", "old.json", "new.json"]),
+        (0, output.to_owned()),
+    );
+    assert_eq!(
+        inputs.run(&["diff", "--exit-code", "old.json", "new.json"]),
+        (1, output.to_owned()),
+    );
+}
+
+#[test]
+fn render() {
+    let inputs = Inputs::new("render");
+    inputs.write("lamp.json", &lamp());
+
+    let (code, output) = inputs.run(&["render", "lamp.json"]);
+    assert_eq!(code, 0);
+    assert!(output.starts_with("# Lamp\n\n## Safety\n\n### Fire hazard\n"));
+    assert!(output.ends_with("| 7 | when brightness is at least 80 |\n"));
+
+    let (code, output) = inputs.run(&["render", "--format", "html", "lamp.json"]);
+    assert_eq!(code, 0);
+    assert!(output.contains("<h1>Lamp</h1>"));
+}
+
+#[test]
+fn input_errors() {
+    let inputs = Inputs::new("input-errors");
+    inputs.write("state.json", &json!({ "properties": { "brightness": 90 } }));
+
+    let (code, output) = inputs.run(&["eval", "missing.json", "state.json"]);
+    assert_eq!(code, 2);
+    assert!(output.starts_with("error: cannot read missing.json: "));
+
+    let (code, output) = inputs.run(&["render", "state.json"]);
+    assert_eq!(code, 2);
+    assert!(output.starts_with("error: invalid state.json: "));
+}
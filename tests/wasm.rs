@@ -0,0 +1,104 @@
+//! Tests of the WebAssembly bindings, as seen from JavaScript.
+//!
+//! They run on `wasm32-unknown-unknown` with `wasm-pack test --node -- --features wasm --test
+//! wasm`, while the behavior of the bindings from Rust is tested in the `wasm` module.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use js_sys::{Array, Function, Reflect};
+use sifis_td::wasm::{risk_catalog, WasmSifis};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const TD: &str = r#"{
+    "sho:hazards": [{
+        "sho:risk": { "@id": "sho:FireHazard", "sho:level": 7 },
+        "sho:conditions": [[{
+            "sho:pointer": "/properties/brightness",
+            "sho:condition": { "sho:value": 80, "sho:op": "ge" }
+        }]]
+    }]
+}"#;
+
+/// Reads a property of a JavaScript object, calling its getter if any.
+fn get(object: &JsValue, property: &str) -> JsValue {
+    Reflect::get(object, &property.into()).unwrap()
+}
+
+/// Calls `f` from JavaScript, returning the exception it throws, if any.
+fn call(f: impl Fn() -> Result<JsValue, JsValue> + 'static) -> Result<JsValue, JsValue> {
+    let closure = Closure::<dyn Fn() -> Result<JsValue, JsValue>>::new(f);
+    closure
+        .as_ref()
+        .unchecked_ref::<Function>()
+        .call0(&JsValue::UNDEFINED)
+}
+
+fn parse(json: &'static str) -> Result<JsValue, JsValue> {
+    call(move || {
+        WasmSifis::new(json)
+            .map(JsValue::from)
+            .map_err(JsValue::from)
+    })
+}
+
+#[wasm_bindgen_test]
+fn evaluate() {
+    let sifis = WasmSifis::new(TD).unwrap();
+    assert_eq!(Array::from(&JsValue::from(sifis.validate())).length(), 0);
+
+    let risks = JsValue::from(
+        sifis
+            .evaluate(r#"{"properties": {"brightness": 90}}"#)
+            .unwrap(),
+    );
+    assert!(Array::is_array(&risks));
+
+    let risks = Array::from(&risks);
+    assert_eq!(risks.length(), 1);
+    let risk = risks.get(0);
+    assert_eq!(get(&get(&risk, "constructor"), "name"), "Risk");
+    assert_eq!(get(&risk, "id"), "sho:FireHazard");
+    assert_eq!(get(&risk, "name"), "Fire hazard");
+    assert_eq!(get(&risk, "category"), "Safety");
+    assert_eq!(get(&risk, "level"), 7);
+
+    let risks = sifis.evaluate(r#"{"properties": {"brightness": 10}}"#);
+    assert_eq!(Array::from(&JsValue::from(risks.unwrap())).length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn errors() {
+    let sifis = parse(TD).unwrap();
+    assert_eq!(get(&get(&sifis, "constructor"), "name"), "Sifis");
+
+    let err = parse("{\n  \"sho:hazards\": [").unwrap_err();
+    assert_eq!(get(&get(&err, "constructor"), "name"), "SifisError");
+    assert_eq!(get(&err, "line"), 2);
+    assert_eq!(get(&err, "column"), 18);
+    assert!(get(&err, "message")
+        .as_string()
+        .unwrap()
+        .starts_with("EOF while parsing"));
+
+    let err = parse(r#"{"sho:hazards": 3}"#).unwrap_err();
+    assert_eq!(get(&get(&err, "constructor"), "name"), "SifisError");
+}
+
+#[wasm_bindgen_test]
+fn catalog() {
+    let catalog = JsValue::from(risk_catalog());
+    assert!(Array::is_array(&catalog));
+
+    let catalog = Array::from(&catalog);
+    assert!(catalog.length() > 0);
+    let detail = catalog.get(0);
+    assert_eq!(get(&get(&detail, "constructor"), "name"), "RiskDetail");
+    assert_eq!(get(&detail, "id"), "sho:AirPoisoning");
+    assert_eq!(get(&detail, "name"), "Air poisoning");
+    assert_eq!(get(&detail, "category"), "Safety");
+    assert_eq!(
+        get(&detail, "description"),
+        "The execution may release toxic gases"
+    );
+}